@@ -0,0 +1,29 @@
+#!/bin/sh
+# Builds the voice pack listed in README.txt with espeak-ng and oggenc
+# (the espeak-ng and vorbis-tools packages). Run it from anywhere, the
+# clips land next to this script. Pass a voice to use another one, e.g.
+#   ./make_clips.sh en-us+f3
+set -e
+
+VOICE=${1:-en-us}
+DIR=$(cd "$(dirname "$0")" && pwd)
+
+NUMBERS="zero one two three four five six seven eight nine ten eleven
+twelve thirteen fourteen fifteen sixteen seventeen eighteen nineteen
+twenty thirty forty fifty sixty seventy eighty ninety hundred thousand
+million negative"
+PROBLEMS="plus minus times divided by square root of squared cubed mod
+percent compared to rounded the nearest dollar dollars cents oh minutes"
+FEEDBACK="correct try again"
+
+clip() {
+    # $1 is the file name, $2 what to say
+    espeak-ng -v "$VOICE" -s 140 -w "$DIR/$1.wav" "$2"
+    oggenc -Q -o "$DIR/$1.ogg" "$DIR/$1.wav"
+    rm "$DIR/$1.wav"
+}
+
+for word in $NUMBERS $PROBLEMS $FEEDBACK; do
+    clip "$word" "$word"
+done
+clip oclock "o'clock"
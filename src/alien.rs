@@ -1,13 +1,41 @@
 use ggez::graphics::{self, Color, DrawParam};
 use ggez::nalgebra as na;
+use rand::*;
 
 use crate::assets::*;
+use crate::banner::*;
+use crate::choice::*;
 use crate::explosion::*;
 use crate::ggez_utility::*;
 use crate::level::*;
+use crate::problem::*;
 use crate::turret::*;
 use ggez::Context;
 
+/// How many times bigger a boss is than an ordinary alien
+pub const BOSS_SCALE: f32 = 2.5;
+// blasts across the hull before the big one, spread over this many millis
+const BOSS_DEBRIS: usize = 8;
+const BOSS_BREAKUP: f32 = 900.0;
+// a hit that doesn't finish a boss off gets a quicker, smaller blast
+const SPARK_DURATION: f32 = 300.0;
+
+/// How much of the screen's width an alien of `kind` takes up
+pub fn alien_width(kind: AlienKind) -> f32 {
+    match kind {
+        AlienKind::Normal => 0.045,
+        AlienKind::Boss { .. } => 0.045 * BOSS_SCALE,
+    }
+}
+
+/// A problem a boss moves on to after a right answer
+pub struct Pending {
+    pub problem: Problem,
+    pub text: graphics::Text,
+    pub choices: Vec<Choice>,
+    pub banner: Option<Banner>,
+}
+
 #[derive(PartialEq)]
 pub enum AlienState {
     Alive,
@@ -19,59 +47,170 @@ pub struct Alien {
     pub speed: f32,
     pub pos: na::Point2<f32>,
     pub text: graphics::Text,
-    pub answer: i32,
+    pub problem: Problem,
+    // only shown when answering by multiple choice
+    pub choices: Vec<Choice>,
+    // the full sentence for problems too long for the label
+    pub banner: Option<Banner>,
     pub explosion: Explosion,
     pub state: AlienState,
     pub src_pixel_width: f32,
     pub src_pixel_height: f32,
+    pub kind: AlienKind,
+    pub pattern: Pattern,
+    // problems still to answer after this one, only bosses have any
+    pub pending: Vec<Pending>,
+    // where it came in, which its pattern moves it either side of, and how
+    // long it's been flying in millis
+    pub home_x: f32,
+    pub age: f32,
+    // the blast from a hit a boss survived
+    pub spark: Option<Explosion>,
+    // blasts across a boss's hull as it goes down, and where on the hull
+    pub debris: Vec<(na::Vector2<f32>, Explosion)>,
 }
 impl Scalable for Alien {
     fn pct_pos(&self) -> na::Point2<f32> {
         self.pos
     }
     fn pct_dimensions(&self) -> (f32, f32) {
-        (0.045, 0.07)
+        if self.is_boss() {
+            (alien_width(self.kind), 0.07 * BOSS_SCALE)
+        } else {
+            (alien_width(self.kind), 0.07)
+        }
     }
     fn src_pixel_dimensions(&self) -> (f32, f32) {
         (self.src_pixel_width, self.src_pixel_height)
     }
 }
 impl Alien {
+    pub fn is_boss(&self) -> bool {
+        !self.kind.is_normal()
+    }
+
+    /// Right answers still needed to destroy it
+    pub fn health(&self) -> usize {
+        if self.state == AlienState::Alive {
+            self.pending.len() + 1
+        } else {
+            0
+        }
+    }
+
     pub fn update(&mut self, turret: &mut Turret, ctx: &mut Context, dt: std::time::Duration) {
         if self.state != AlienState::Dead {
             let sec = dt.as_millis() as f32 / 100000.0;
-            if self.pos[1] < 0.07 {
-                self.pos = self.pos + na::Vector2::new(0.0, self.speed * 3. * sec);
+            // formations come in at their own pace so the rows stay together
+            let fall = if self.pos[1] < 0.07 && self.pattern != Pattern::Formation {
+                3.0
             } else {
-                self.pos = self.pos + na::Vector2::new(0.0, self.speed * sec);
+                self.pattern.fall(self.age, self.pos[1])
+            };
+            self.pos += na::Vector2::new(0.0, self.speed * fall * sec);
+            if self.state == AlienState::Alive {
+                self.age += dt.as_millis() as f32;
+                if self.pattern.reach() > 0.0 {
+                    let half = self.pct_dimensions().0 / 2.0;
+                    let x = self.home_x + self.pattern.drift(self.age);
+                    self.pos[0] = x.clamp(half, 1.0 - half);
+                }
+            }
+            if let Some(spark) = &mut self.spark {
+                spark.update(ctx, dt);
+                if spark.finished() {
+                    self.spark = None;
+                    turret.state = TurretState::Resting;
+                }
             }
             if self.state == AlienState::Exploding {
                 self.explosion.update(ctx, dt);
+                for (_, debris) in &mut self.debris {
+                    debris.update(ctx, dt);
+                }
             }
-            if self.explosion.elapsed > self.explosion.duration {
+            if self.explosion.finished() {
                 self.state = AlienState::Dead;
                 turret.state = TurretState::Resting;
             }
         }
     }
 
-    pub fn draw(&mut self, ctx: &mut Context, assets: &mut Assets) {
+    /// Takes a right answer. A boss with problems left moves on to the next
+    /// one, anything else starts exploding. Returns whether it was destroyed.
+    pub fn take_hit(&mut self) -> bool {
+        if self.pending.is_empty() {
+            self.state = AlienState::Exploding;
+            if self.is_boss() {
+                self.break_up();
+            }
+            return true;
+        }
+        let next = self.pending.remove(0);
+        self.problem = next.problem;
+        self.text = next.text;
+        self.choices = next.choices;
+        self.banner = next.banner;
+        let mut spark = Explosion::new(0.0, self.pos);
+        spark.duration = SPARK_DURATION;
+        self.spark = Some(spark);
+        false
+    }
+
+    // small blasts all over the hull, then one the size of the ship
+    fn break_up(&mut self) {
+        let mut rng = rand::thread_rng();
+        let (w, h) = self.pct_dimensions();
+        self.debris = (0..BOSS_DEBRIS)
+            .map(|i| {
+                let offset = na::Vector2::new(
+                    rng.gen_range(-w / 2.0, w / 2.0),
+                    rng.gen_range(-h / 2.0, h / 2.0),
+                );
+                let start = i as f32 * BOSS_BREAKUP / BOSS_DEBRIS as f32;
+                (offset, Explosion::new(start, self.pos + offset))
+            })
+            .collect();
+        self.explosion = Explosion::new(BOSS_BREAKUP, self.pos);
+        self.explosion.scale = BOSS_SCALE;
+    }
+
+    /// Where multiple choice answers go, under a boss's health bar rather
+    /// than on top of it
+    pub fn choices_pos(&self) -> na::Point2<f32> {
+        if self.is_boss() {
+            let (_, h) = self.pct_dimensions();
+            self.pos + na::Vector2::new(0.0, h / 2.0 - 0.02)
+        } else {
+            self.pos
+        }
+    }
+
+    /// Whether a click or tap at `pos`, in pixels, lands on this alien. Leaves
+    /// some slack around the ship for small fingers.
+    pub fn hit(&self, pos: na::Point2<f32>, screen: (f32, f32)) -> bool {
+        let center = self.pixel_pos(screen);
+        let (w, h) = self.dest_pixel_dimensions(screen);
+        (pos[0] - center[0]).abs() <= w * 0.75 && (pos[1] - center[1]).abs() <= h * 0.75
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, assets: &mut Assets, text_scale: f32) {
         if self.state != AlienState::Dead {
-            if self.explosion.elapsed < self.explosion.duration / 2.0 {
+            let explosion = &self.explosion;
+            if explosion.elapsed - explosion.start_time < explosion.duration / 2.0 {
                 let params = DrawParam::new()
                     .color(Color::from((255, 255, 255, 255)))
                     .dest(self.pixel_pos(graphics::size(ctx)))
                     .scale(self.scale(graphics::size(ctx)))
                     .offset(na::Point2::new(0.5, 0.5));
-                let img = match self.operation {
-                    Operation::Add => &assets.add_ship,
-                    Operation::Subtract => &assets.sub_ship,
-                    Operation::Multiply => &assets.mul_ship,
-                    Operation::Divide => &assets.div_ship,
+                let image = if self.is_boss() {
+                    &assets.boss_ship
+                } else {
+                    assets.ship(self.operation)
                 };
-                let _ = graphics::draw(ctx, img, params);
+                let _ = graphics::draw(ctx, image, params);
 
-                let tw = self.text.width(ctx) as f32;
+                let tw = self.text.width(ctx) as f32 * text_scale;
                 let (sw, sh) = self.dest_pixel_dimensions(graphics::size(ctx));
                 let offsetx = -sw / 2.0 + (sw - tw) / 2.0;
                 let offsety = -sh / 1.2;
@@ -80,14 +219,53 @@ impl Alien {
 
                 let text_param = DrawParam::new()
                     .color(Color::from((255, 255, 255, 255)))
-                    .dest(self.pixel_pos(graphics::size(ctx)) + offset);
+                    .dest(self.pixel_pos(graphics::size(ctx)) + offset)
+                    .scale(na::Vector2::new(text_scale, text_scale));
                 let _ = graphics::draw(ctx, &self.text, text_param);
+
+                if self.is_boss() && self.state == AlienState::Alive {
+                    self.draw_health(ctx);
+                }
             }
         }
 
+        if let Some(spark) = &mut self.spark {
+            spark.pos = self.pos;
+            spark.draw(ctx, assets);
+        }
         if self.state == AlienState::Exploding {
+            for (offset, debris) in &mut self.debris {
+                debris.pos = self.pos + *offset;
+                debris.draw(ctx, assets);
+            }
             self.explosion.pos = self.pct_pos();
             self.explosion.draw(ctx, assets);
         }
     }
+
+    // a bar under the ship that empties with each right answer
+    fn draw_health(&self, ctx: &mut Context) {
+        let screen = graphics::size(ctx);
+        let center = self.pixel_pos(screen);
+        let (w, h) = self.dest_pixel_dimensions(screen);
+        let bar = graphics::Rect::new(
+            center[0] - w * 0.4,
+            center[1] + h / 2.0,
+            w * 0.8,
+            0.012 * screen.1,
+        );
+        let health = self.health() as f32 / self.kind.hits() as f32;
+        let left = graphics::Rect::new(bar.x, bar.y, bar.w * health, bar.h);
+        let colors = [
+            (bar, Color::from((80, 80, 80, 200))),
+            (left, Color::from((220, 40, 40, 255))),
+        ];
+        for (rect, color) in colors.iter() {
+            if let Ok(mesh) =
+                graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), *rect, *color)
+            {
+                let _ = graphics::draw(ctx, &mesh, DrawParam::default());
+            }
+        }
+    }
 }
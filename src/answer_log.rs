@@ -0,0 +1,60 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::choice::*;
+
+const ANSWER_LOG_FILE: &str = "answer_log.csv";
+const ANSWER_LOG_HEADER: &str = "timestamp,player,level,wave,problem,answer,picked,misconception\n";
+
+/// Appends a multiple choice pick to the answer log so teachers can see
+/// which mistakes come up, and who made them. A correct pick is logged as
+/// "Correct".
+pub fn log_choice(
+    player: &str,
+    level: &str,
+    wave: usize,
+    problem: &str,
+    correct: &str,
+    choice: &Choice,
+) {
+    fn log_helper(line: String) -> Result<(), String> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(ANSWER_LOG_FILE)
+            .map_err(|_| "couldn't open the answer log")?;
+        // a new log starts with the column names
+        if file.metadata().map(|m| m.len() == 0).unwrap_or(false) {
+            file.write_all(ANSWER_LOG_HEADER.as_bytes())
+                .map_err(|_| "couldn't write to the answer log")?;
+        }
+        file.write_all(line.as_bytes())
+            .map_err(|_| "couldn't write to the answer log")?;
+        Ok(())
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let misconception = match choice.misconception {
+        Some(misconception) => format!("{:?}", misconception),
+        None => "Correct".to_string(),
+    };
+    // problems can contain commas, so quote the free text columns
+    let line = format!(
+        "{},\"{}\",\"{}\",{},\"{}\",{},{},{}\n",
+        timestamp,
+        player.replace('"', "'"),
+        level.replace('"', "'"),
+        wave + 1,
+        problem.replace('"', "'"),
+        correct,
+        choice.answer,
+        misconception
+    );
+    if let Err(msg) = log_helper(line) {
+        println!("{}", msg);
+    }
+}
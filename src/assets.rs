@@ -1,12 +1,25 @@
-use ggez::audio;
 use ggez::graphics::{self};
 use ggez::Context;
 
+use crate::audio::*;
+use crate::level::*;
+use crate::word_problem::*;
+
 pub struct Assets {
     pub add_ship: graphics::Image,
     pub sub_ship: graphics::Image,
     pub mul_ship: graphics::Image,
     pub div_ship: graphics::Image,
+    pub pow_ship: graphics::Image,
+    pub sqrt_ship: graphics::Image,
+    pub mod_ship: graphics::Image,
+    pub pct_ship: graphics::Image,
+    pub cmp_ship: graphics::Image,
+    pub round_ship: graphics::Image,
+    pub word_ship: graphics::Image,
+    pub money_ship: graphics::Image,
+    pub clock_ship: graphics::Image,
+    pub boss_ship: graphics::Image,
     pub crosshair: graphics::Image,
     pub title_font: graphics::Font,
     pub main_font: graphics::Font,
@@ -16,22 +29,37 @@ pub struct Assets {
     pub stars1: graphics::Image,
     pub stars2: graphics::Image,
     pub explosion: graphics::Image,
-    pub explosion_sound: audio::Source,
-    pub clap_sound: audio::Source,
-    pub launch_sound: audio::Source,
-    pub fail_sound: audio::Source,
-    pub laser_sound: audio::Source,
-    pub music: audio::Source,
+    pub audio: Audio,
+    pub word_problems: TemplateCache,
+}
+
+// The newer operations don't all have art yet, so borrow a related ship
+// until a sprite for them is dropped into resources
+fn image_or_fallback(ctx: &mut Context, path: &str, fallback: &str) -> graphics::Image {
+    match graphics::Image::new(ctx, path) {
+        Ok(image) => image,
+        Err(_) => graphics::Image::new(ctx, fallback).unwrap(),
+    }
 }
 
 impl Assets {
-    pub fn new(ctx: &mut Context, start_bg: String) -> Assets {
+    pub fn new(ctx: &mut Context, start_bg: String, word_problems: TemplateCache) -> Assets {
         Assets {
             add_ship: graphics::Image::new(ctx, "/add-ship.png").unwrap(),
             sub_ship: graphics::Image::new(ctx, "/sub-ship.png").unwrap(),
             mul_ship: graphics::Image::new(ctx, "/mul-ship.png").unwrap(),
             crosshair: graphics::Image::new(ctx, "/crosshair.png").unwrap(),
             div_ship: graphics::Image::new(ctx, "/div-ship.png").unwrap(),
+            pow_ship: image_or_fallback(ctx, "/pow-ship.png", "/mul-ship.png"),
+            sqrt_ship: image_or_fallback(ctx, "/sqrt-ship.png", "/div-ship.png"),
+            mod_ship: image_or_fallback(ctx, "/mod-ship.png", "/div-ship.png"),
+            pct_ship: image_or_fallback(ctx, "/pct-ship.png", "/mul-ship.png"),
+            cmp_ship: image_or_fallback(ctx, "/cmp-ship.png", "/sub-ship.png"),
+            round_ship: image_or_fallback(ctx, "/round-ship.png", "/add-ship.png"),
+            word_ship: image_or_fallback(ctx, "/word-ship.png", "/add-ship.png"),
+            money_ship: image_or_fallback(ctx, "/money-ship.png", "/add-ship.png"),
+            clock_ship: image_or_fallback(ctx, "/clock-ship.png", "/sub-ship.png"),
+            boss_ship: graphics::Image::new(ctx, "/boss-ship.png").unwrap(),
             title_font: graphics::Font::new(ctx, "/title.ttf").unwrap(),
             main_font: graphics::Font::new(ctx, "/main.ttf").unwrap(),
             number_font: graphics::Font::new(ctx, "/number.ttf").unwrap(),
@@ -40,12 +68,26 @@ impl Assets {
             stars1: graphics::Image::new(ctx, "/stars1.png").unwrap(),
             stars2: graphics::Image::new(ctx, "/stars2.png").unwrap(),
             explosion: graphics::Image::new(ctx, "/explosion.png").unwrap(),
-            explosion_sound: audio::Source::new(ctx, "/explosion.wav").unwrap(),
-            clap_sound: audio::Source::new(ctx, "/clap.ogg").unwrap(),
-            launch_sound: audio::Source::new(ctx, "/launch.wav").unwrap(),
-            fail_sound: audio::Source::new(ctx, "/fail.ogg").unwrap(),
-            laser_sound: audio::Source::new(ctx, "/laser.ogg").unwrap(),
-            music: audio::Source::new(ctx, "/music.mp3").unwrap(),
+            audio: Audio::new(ctx),
+            word_problems,
+        }
+    }
+
+    pub fn ship(&self, operation: Operation) -> &graphics::Image {
+        match operation {
+            Operation::Add => &self.add_ship,
+            Operation::Subtract => &self.sub_ship,
+            Operation::Multiply => &self.mul_ship,
+            Operation::Divide => &self.div_ship,
+            Operation::Power => &self.pow_ship,
+            Operation::SquareRoot => &self.sqrt_ship,
+            Operation::Modulo => &self.mod_ship,
+            Operation::PercentOf => &self.pct_ship,
+            Operation::Compare => &self.cmp_ship,
+            Operation::RoundTen | Operation::RoundHundred => &self.round_ship,
+            Operation::WordProblem => &self.word_ship,
+            Operation::MoneySum | Operation::MakeChange => &self.money_ship,
+            Operation::TimeAfter | Operation::TimeElapsed => &self.clock_ship,
         }
     }
 }
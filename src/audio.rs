@@ -0,0 +1,209 @@
+use ggez::audio::{self, SoundSource};
+use ggez::{filesystem, Context};
+use std::collections::{HashMap, VecDeque};
+
+use crate::voice::*;
+
+pub const DEFAULT_MUSIC_FILE: &str = "/music.mp3";
+
+// how far the music drops while a message is up, and how fast it gets there
+const DUCK_LEVEL: f32 = 0.4;
+const DUCK_TIME: f32 = 250.0;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Sound {
+    Explosion,
+    Clap,
+    Launch,
+    Fail,
+    Laser,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Bus {
+    Music,
+    Sfx,
+    Voice,
+}
+
+struct Track {
+    file: String,
+    source: audio::Source,
+}
+
+/// Owns every sound in the game. Sounds play on the music, effects or
+/// voice bus, each with its own volume.
+pub struct Audio {
+    explosion: audio::Source,
+    clap: audio::Source,
+    launch: audio::Source,
+    fail: audio::Source,
+    laser: audio::Source,
+    music: Option<Track>,
+    // the track being faded out during a crossfade
+    fading: Option<Track>,
+    // (elapsed, duration) in millis
+    crossfade: (f32, f32),
+    volumes: [f32; 3],
+    ducked: bool,
+    duck: f32,
+    // words waiting to be spoken, one clip plays at a time
+    speech: VecDeque<String>,
+    voice: Option<audio::Source>,
+    // None for words the voice pack doesn't have
+    clips: HashMap<String, Option<audio::SoundData>>,
+}
+
+impl Audio {
+    pub fn new(ctx: &mut Context) -> Audio {
+        Audio {
+            explosion: audio::Source::new(ctx, "/explosion.wav").unwrap(),
+            clap: audio::Source::new(ctx, "/clap.ogg").unwrap(),
+            launch: audio::Source::new(ctx, "/launch.wav").unwrap(),
+            fail: audio::Source::new(ctx, "/fail.ogg").unwrap(),
+            laser: audio::Source::new(ctx, "/laser.ogg").unwrap(),
+            music: None,
+            fading: None,
+            crossfade: (0.0, 0.0),
+            volumes: [1.0; 3],
+            ducked: false,
+            duck: 1.0,
+            speech: VecDeque::new(),
+            voice: None,
+            clips: HashMap::new(),
+        }
+    }
+
+    pub fn volume(&self, bus: Bus) -> f32 {
+        self.volumes[bus as usize]
+    }
+
+    pub fn set_volume(&mut self, bus: Bus, volume: f32) {
+        self.volumes[bus as usize] = volume;
+    }
+
+    pub fn play(&mut self, sound: Sound) {
+        let volume = self.volume(Bus::Sfx);
+        let source = match sound {
+            Sound::Explosion => &mut self.explosion,
+            Sound::Clap => &mut self.clap,
+            Sound::Launch => &mut self.launch,
+            Sound::Fail => &mut self.fail,
+            Sound::Laser => &mut self.laser,
+        };
+        // detached sounds get a fresh sink every time they play, so the
+        // volume has to be set again before each one
+        source.set_volume(volume);
+        let _ = source.play_detached();
+    }
+
+    /// Switches to the track in `file`, fading between the two over
+    /// `fade` millis. Nothing happens if it's already playing.
+    pub fn play_music(&mut self, ctx: &mut Context, file: &str, fade: f32) {
+        if self.music.as_ref().map(|track| track.file.as_str()) == Some(file) {
+            return;
+        }
+        let mut source = match audio::Source::new(ctx, file) {
+            Ok(source) => source,
+            Err(e) => {
+                println!("couldn't load music {}\n{}", file, e);
+                return;
+            }
+        };
+        source.set_repeat(true);
+        // play swaps in a new sink, so the volume has to go on afterwards
+        let _ = source.play();
+        source.set_volume(0.0);
+        self.fading = self.music.take();
+        self.music = Some(Track {
+            file: file.to_string(),
+            source,
+        });
+        self.crossfade = (0.0, fade);
+        self.apply_music_volume();
+    }
+
+    /// Queues `words` to be read out after anything already queued
+    pub fn speak(&mut self, words: Vec<String>) {
+        self.speech.extend(words);
+    }
+
+    /// Drops queued words, the word being spoken still finishes
+    pub fn interrupt(&mut self) {
+        self.speech.clear();
+    }
+
+    fn clip(&mut self, ctx: &mut Context, word: &str) -> Option<audio::Source> {
+        let data = self
+            .clips
+            .entry(word.to_string())
+            .or_insert_with(|| {
+                let path = clip_path(word);
+                if filesystem::exists(ctx, &path) {
+                    audio::SoundData::new(ctx, &path).ok()
+                } else {
+                    println!("no voice clip for {}", word);
+                    None
+                }
+            })
+            .clone()?;
+        audio::Source::from_data(ctx, data).ok()
+    }
+
+    fn update_speech(&mut self, ctx: &mut Context) {
+        if let Some(voice) = &self.voice {
+            if !voice.stopped() {
+                return;
+            }
+        }
+        self.voice = None;
+        while let Some(word) = self.speech.pop_front() {
+            if let Some(mut source) = self.clip(ctx, &word) {
+                let _ = source.play();
+                source.set_volume(self.volume(Bus::Voice));
+                self.voice = Some(source);
+                break;
+            }
+        }
+    }
+
+    /// Music drops while messages are on screen so they can be heard
+    pub fn set_ducked(&mut self, ducked: bool) {
+        self.ducked = ducked;
+    }
+
+    pub fn update(&mut self, ctx: &mut Context, dt: std::time::Duration) {
+        self.update_speech(ctx);
+        let millis = dt.as_millis() as f32;
+        // the voice ducks the music too
+        let target = if self.ducked || self.voice.is_some() {
+            DUCK_LEVEL
+        } else {
+            1.0
+        };
+        let step = (millis / DUCK_TIME).min(1.0);
+        self.duck += (target - self.duck) * step;
+
+        self.crossfade.0 += millis;
+        if self.crossfade.0 >= self.crossfade.1 {
+            self.fading = None;
+        }
+        self.apply_music_volume();
+    }
+
+    fn apply_music_volume(&mut self) {
+        let volume = self.volume(Bus::Music) * self.duck;
+        let (elapsed, duration) = self.crossfade;
+        let pct = if duration > 0.0 {
+            (elapsed / duration).min(1.0)
+        } else {
+            1.0
+        };
+        if let Some(track) = &mut self.music {
+            track.source.set_volume(volume * pct);
+        }
+        if let Some(track) = &mut self.fading {
+            track.source.set_volume(volume * (1.0 - pct));
+        }
+    }
+}
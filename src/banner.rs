@@ -0,0 +1,77 @@
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::nalgebra as na;
+use ggez::Context;
+
+use crate::assets::*;
+use crate::ggez_utility::*;
+
+// laid out at 1920 wide like the rest of the text, then scaled to the window
+const BANNER_WIDTH: f32 = 1920.0 * 0.8;
+const BANNER_TOP: f32 = 0.02;
+const BANNER_PADDING: f32 = 0.01;
+
+/// A full sentence drawn across the top of the screen, used for problems
+/// that are too long to fit on an alien.
+pub struct Banner {
+    pub text: graphics::Text,
+    // the text size setting the text was last wrapped for
+    text_scale: f32,
+}
+
+impl Banner {
+    pub fn new(sentence: String, assets: &Assets) -> Banner {
+        let mut text = graphics::Text::new((sentence, assets.main_font, 48.0));
+        text.set_bounds(
+            na::Point2::new(BANNER_WIDTH, f32::INFINITY),
+            graphics::Align::Center,
+        );
+        Banner {
+            text,
+            text_scale: 1.0,
+        }
+    }
+
+    /// Draws the banner with a line down to `target`, the pixel position of
+    /// the alien it belongs to.
+    pub fn draw(&mut self, target: na::Point2<f32>, text_scale: f32, ctx: &mut Context) {
+        // bigger text wraps sooner so the banner stays the same width
+        if self.text_scale != text_scale {
+            self.text_scale = text_scale;
+            self.text.set_bounds(
+                na::Point2::new(BANNER_WIDTH / text_scale, f32::INFINITY),
+                graphics::Align::Center,
+            );
+        }
+        let screen = graphics::size(ctx);
+        let scale = screen.0 / 1920.0 * text_scale;
+        let (_, text_h) = self.text.dimensions(ctx);
+        let text_h = text_h as f32 * scale;
+        let padding = BANNER_PADDING * screen.1;
+
+        let rect = graphics::Rect::new(
+            screen.0 * 0.1 - padding,
+            screen.1 * BANNER_TOP,
+            screen.0 * 0.8 + padding * 2.0,
+            text_h + padding * 2.0,
+        );
+        let bottom = na::Point2::new(screen.0 / 2.0, rect.y + rect.h);
+        if let Ok(line) = graphics::Mesh::new_line(ctx, &[bottom, target], 2.0, BLUE) {
+            let _ = graphics::draw(ctx, &line, DrawParam::default());
+        }
+        if let Ok(background) = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            rect,
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        ) {
+            let _ = graphics::draw(ctx, &background, DrawParam::default());
+        }
+        let _ = graphics::draw(
+            ctx,
+            &self.text,
+            DrawParam::new()
+                .dest(na::Point2::new(screen.0 * 0.1, rect.y + padding))
+                .scale(na::Vector2::new(scale, scale)),
+        );
+    }
+}
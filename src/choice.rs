@@ -0,0 +1,218 @@
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::nalgebra as na;
+use ggez::Context;
+use rand::seq::SliceRandom;
+use rand::*;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::*;
+use crate::ggez_utility::*;
+use crate::problem::*;
+
+pub const MAX_CHOICES: usize = 4;
+const CHOICE_WIDTH: f32 = 0.06;
+const CHOICE_HEIGHT: f32 = 0.045;
+const CHOICE_GAP: f32 = 0.01;
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum InputMode {
+    Typed,
+    MultipleChoice,
+}
+
+pub const INPUT_MODES: [InputMode; 2] = [InputMode::Typed, InputMode::MultipleChoice];
+
+impl InputMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputMode::Typed => "Type Answers",
+            InputMode::MultipleChoice => "Choose Answers",
+        }
+    }
+}
+
+/// Why a wrong choice is wrong, so teachers can see what went astray
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Misconception {
+    OffByOne,
+    WrongOperation,
+    DigitSwap,
+    ReversedSymbol,
+    Guess,
+}
+
+pub struct Choice {
+    pub answer: Answer,
+    // None for the correct answer
+    pub misconception: Option<Misconception>,
+    pub label: graphics::Text,
+}
+
+fn swap_digits(n: i32) -> Option<i32> {
+    let digits: String = n.abs().to_string().chars().rev().collect();
+    let swapped = digits.parse::<i32>().ok()? * n.signum();
+    if n.abs() >= 10 && swapped != n {
+        Some(swapped)
+    } else {
+        None
+    }
+}
+
+// answers that come from applying the wrong operation to the same numbers,
+// amounts like money only get added and subtracted since a product of two
+// prices isn't a mistake anyone makes
+fn wrong_operations(a: i32, b: i32, amounts: bool) -> Vec<i32> {
+    let mut results = vec![a + b, a - b];
+    if amounts {
+        return results;
+    }
+    results.push(a.saturating_mul(b));
+    if b != 0 && a % b == 0 {
+        results.push(a / b);
+    }
+    results
+}
+
+fn distractors<R: Rng>(problem: &Problem, rng: &mut R) -> Vec<(Answer, Misconception)> {
+    let mut candidates = Vec::new();
+    match problem.answer {
+        Answer::Symbol(symbol) => {
+            // pointing the alligator's mouth the wrong way
+            match symbol {
+                '<' => candidates.push((Answer::Symbol('>'), Misconception::ReversedSymbol)),
+                '>' => candidates.push((Answer::Symbol('<'), Misconception::ReversedSymbol)),
+                _ => (),
+            }
+            for other in &['<', '>', '='] {
+                candidates.push((Answer::Symbol(*other), Misconception::Guess));
+            }
+        }
+        Answer::Time(hour, minute) => {
+            let next = if hour == 12 { 1 } else { hour + 1 };
+            let previous = if hour == 1 { 12 } else { hour - 1 };
+            candidates.push((Answer::Time(next, minute), Misconception::OffByOne));
+            candidates.push((Answer::Time(previous, minute), Misconception::OffByOne));
+            // mixing up which hand is which
+            if minute % 5 == 0 && minute >= 5 {
+                candidates.push((
+                    Answer::Time(minute / 5, (hour % 12) * 5),
+                    Misconception::DigitSwap,
+                ));
+            }
+            for _ in 0..MAX_CHOICES {
+                let minute = (minute + rng.gen_range(1, 12) * 5) % 60;
+                candidates.push((Answer::Time(hour, minute), Misconception::Guess));
+            }
+        }
+        Answer::Number(n) | Answer::Cents(n) => {
+            let wrap = |n| match problem.answer {
+                Answer::Cents(_) => Answer::Cents(n),
+                _ => Answer::Number(n),
+            };
+            // a coin off counts as off by one when counting money
+            let (unit, amounts) = match problem.answer {
+                Answer::Cents(_) => (5, true),
+                _ => (1, false),
+            };
+            candidates.push((wrap(n + unit), Misconception::OffByOne));
+            candidates.push((wrap(n - unit), Misconception::OffByOne));
+            if let Some((a, b)) = problem.operands {
+                for wrong in wrong_operations(a, b, amounts) {
+                    candidates.push((wrap(wrong), Misconception::WrongOperation));
+                }
+            }
+            if let Some(swapped) = swap_digits(n) {
+                candidates.push((wrap(swapped), Misconception::DigitSwap));
+            }
+            for _ in 0..MAX_CHOICES {
+                let offset = rng.gen_range(2, 6) * unit;
+                let guess = if rng.gen() { n + offset } else { n - offset };
+                candidates.push((wrap(guess), Misconception::Guess));
+            }
+        }
+    }
+
+    // keep the most telling mistakes, but shuffle within each kind so the
+    // same misconception isn't always first
+    candidates.shuffle(rng);
+    candidates.sort_by_key(|(_, misconception)| *misconception == Misconception::Guess);
+    let mut picked: Vec<(Answer, Misconception)> = Vec::new();
+    for (answer, misconception) in candidates {
+        if answer != problem.answer && picked.iter().all(|(other, _)| *other != answer) {
+            picked.push((answer, misconception));
+        }
+        if picked.len() == MAX_CHOICES - 1 {
+            break;
+        }
+    }
+    picked
+}
+
+pub fn gen_choices<R: Rng>(problem: &Problem, assets: &Assets, rng: &mut R) -> Vec<Choice> {
+    let mut answers = vec![(problem.answer, None)];
+    for (answer, misconception) in distractors(problem, rng) {
+        answers.push((answer, Some(misconception)));
+    }
+    answers.shuffle(rng);
+    answers
+        .into_iter()
+        .enumerate()
+        .map(|(i, (answer, misconception))| Choice {
+            answer,
+            misconception,
+            label: graphics::Text::new((
+                format!("{}: {}", i + 1, answer),
+                assets.number_font,
+                24.0,
+            )),
+        })
+        .collect()
+}
+
+/// Where choice `index` of `count` is drawn for an alien at `pos`, in pixels
+pub fn choice_rect(
+    index: usize,
+    count: usize,
+    pos: na::Point2<f32>,
+    screen: (f32, f32),
+) -> graphics::Rect {
+    let w = CHOICE_WIDTH * screen.0;
+    let h = CHOICE_HEIGHT * screen.1;
+    let gap = CHOICE_GAP * screen.0;
+    let total = count as f32 * w + (count as f32 - 1.0) * gap;
+    let left = (pos[0] * screen.0 - total / 2.0)
+        .max(0.0)
+        .min(screen.0 - total);
+    graphics::Rect::new(
+        left + index as f32 * (w + gap),
+        pos[1] * screen.1 + 0.05 * screen.1,
+        w,
+        h,
+    )
+}
+
+pub fn draw_choices(choices: &[Choice], pos: na::Point2<f32>, ctx: &mut Context) {
+    let screen = graphics::size(ctx);
+    for (i, choice) in choices.iter().enumerate() {
+        let rect = choice_rect(i, choices.len(), pos, screen);
+        if let Ok(background) = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            rect,
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        ) {
+            let _ = graphics::draw(ctx, &background, DrawParam::default());
+        }
+        if let Ok(border) =
+            graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.0), rect, BLUE)
+        {
+            let _ = graphics::draw(ctx, &border, DrawParam::default());
+        }
+        let (tw, th) = choice.label.dimensions(ctx);
+        let dest = na::Point2::new(
+            rect.x + (rect.w - tw as f32) / 2.0,
+            rect.y + (rect.h - th as f32) / 2.0,
+        );
+        let _ = graphics::draw(ctx, &choice.label, DrawParam::new().color(WHITE).dest(dest));
+    }
+}
@@ -0,0 +1,70 @@
+use crate::score::*;
+
+pub const MIN_PLAYERS: usize = 2;
+
+/// Profiles taking turns a wave each on one machine. While a player is up
+/// their score is swapped into the game's, so hits count the usual way.
+pub struct CoOp {
+    /// Profile names in turn order
+    pub players: Vec<String>,
+    pub turn: usize,
+    pub scores: Vec<Score>,
+    /// Index into the campaign levels
+    pub level: usize,
+}
+
+impl CoOp {
+    pub fn new() -> CoOp {
+        CoOp {
+            players: Vec::new(),
+            turn: 0,
+            scores: Vec::new(),
+            level: 0,
+        }
+    }
+
+    pub fn playing(&self, name: &str) -> bool {
+        self.players.iter().any(|player| player == name)
+    }
+
+    /// Adds `name` to the end of the turn order, or takes them out
+    pub fn toggle(&mut self, name: &str) {
+        match self.players.iter().position(|player| player == name) {
+            Some(i) => {
+                self.players.remove(i);
+            }
+            None => self.players.push(name.to_string()),
+        }
+    }
+
+    pub fn ready(&self) -> bool {
+        self.players.len() >= MIN_PLAYERS
+    }
+
+    /// Fresh scores with the first player up
+    pub fn start(&mut self, lives: usize) {
+        self.turn = 0;
+        self.scores = self.players.iter().map(|_| Score::new(lives)).collect();
+    }
+
+    pub fn current(&self) -> &str {
+        &self.players[self.turn]
+    }
+
+    /// Puts `score` back with the player who earned it and swaps in the next
+    /// player's
+    pub fn next_turn(&mut self, score: &mut Score) {
+        std::mem::swap(score, &mut self.scores[self.turn]);
+        self.turn = (self.turn + 1) % self.players.len();
+        std::mem::swap(score, &mut self.scores[self.turn]);
+    }
+
+    /// Puts `score` back with the player who earned it once the run is over
+    pub fn finish(&mut self, score: &mut Score) {
+        std::mem::swap(score, &mut self.scores[self.turn]);
+    }
+
+    pub fn team_score(&self) -> u32 {
+        self.scores.iter().map(|score| score.points).sum()
+    }
+}
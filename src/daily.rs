@@ -0,0 +1,187 @@
+use chrono::{Datelike, Local, NaiveDate};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+
+use crate::level::*;
+
+const DAILY_FILE: &str = "daily.json";
+pub const DAILY_WAVES: usize = 5;
+/// Everyone plays the daily challenge at this difficulty, so the problems
+/// match no matter what was picked on the title screen
+pub const DAILY_DIFFICULTY: usize = 1;
+const DAILY_OPERATIONS: [Operation; 8] = [
+    Operation::Add,
+    Operation::Subtract,
+    Operation::Multiply,
+    Operation::Divide,
+    Operation::Modulo,
+    Operation::RoundTen,
+    Operation::MoneySum,
+    Operation::TimeAfter,
+];
+
+/// Seeded problems have to come out the same for every player, so they use
+/// a named generator rather than `StdRng`, which can change between rand
+/// versions. rand and rand_pcg are pinned in Cargo.toml for the same reason
+pub type SeededRng = Pcg32;
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+// the same day gives the same seed on every machine, that's all the sharing needed
+fn daily_seed(date: NaiveDate) -> u64 {
+    date.num_days_from_ce() as u64
+}
+
+/// The rng for placing one wave's aliens, restarting a wave brings back the
+/// same problems
+pub fn wave_rng(date: NaiveDate, wave: usize) -> SeededRng {
+    seeded_rng(daily_seed(date), wave)
+}
+
+/// Like `wave_rng` for anything else that needs everyone to get the same aliens
+pub fn seeded_rng(seed: u64, wave: usize) -> SeededRng {
+    SeededRng::seed_from_u64(seed.wrapping_mul(100).wrapping_add(wave as u64))
+}
+
+/// The waves for `date`, each a mix of a few operations and a little faster
+/// than the last
+pub fn daily_level(date: NaiveDate) -> Level {
+    Level {
+        waves: seeded_waves(daily_seed(date)),
+        background_file: "/spacebg4.jpg".to_string(),
+        title: "Daily Challenge".to_string(),
+        unlocked: [true; 4],
+        music_file: None,
+        stars: [0; 4],
+        unlock_rules: Vec::new(),
+    }
+}
+
+/// `DAILY_WAVES` waves that only depend on `seed`
+pub fn seeded_waves(seed: u64) -> Vec<Wave> {
+    let mut rng = SeededRng::seed_from_u64(seed);
+    (0..DAILY_WAVES)
+        .map(|number| {
+            let count = rng.gen_range(1, 4);
+            let operations: Vec<Operation> = DAILY_OPERATIONS
+                .choose_multiple(&mut rng, count)
+                .cloned()
+                .collect();
+            let groups = operations
+                .iter()
+                .map(|operation| WaveGroup {
+                    operation: *operation,
+                    speed: 2.5 + 0.5 * number as f32,
+                    num_ships: ((5 + 2 * number) / count).max(2),
+                    min_number: 0,
+                    max_number: 5 + number as i32,
+                    template_file: None,
+                    kind: AlienKind::Normal,
+                    pattern: Pattern::Straight,
+                })
+                .collect();
+            Wave { groups }
+        })
+        .collect()
+}
+
+/// The first of the month `months` away from the one `first` is in
+pub fn add_months(first: NaiveDate, months: i32) -> NaiveDate {
+    let index = first.year() * 12 + first.month0() as i32 + months;
+    NaiveDate::from_ymd_opt(index.div_euclid(12), index.rem_euclid(12) as u32 + 1, 1)
+        .unwrap_or(first)
+}
+
+/// One profile's best run of the challenge on one day
+#[derive(Deserialize, Serialize)]
+pub struct DailyResult {
+    /// e.g. "2026-10-18"
+    pub date: String,
+    pub profile: String,
+    pub score: u32,
+    pub waves: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DailyHistory {
+    pub results: Vec<DailyResult>,
+}
+
+impl DailyHistory {
+    pub fn load_from_file() -> DailyHistory {
+        //if any of this fails, start with an empty calendar
+        fn load_helper() -> Result<DailyHistory, String> {
+            let mut file = File::open(DAILY_FILE).map_err(|e| format!("file not found\n {}", e))?;
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)
+                .map_err(|e| format!("file could not be read\n{}", e))?;
+            let history: DailyHistory = serde_json::from_slice(&buffer[..])
+                .map_err(|e| format!("file not valid\n{}", e))?;
+            Ok(history)
+        }
+
+        match load_helper() {
+            Ok(history) => history,
+            Err(msg) => {
+                println!(
+                    "Error loading daily challenge file.\nStarting fresh\n{}",
+                    msg
+                );
+                DailyHistory {
+                    results: Vec::new(),
+                }
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        fn save_helper(history: &DailyHistory) -> Result<(), String> {
+            let serialized = serde_json::to_string_pretty(history)
+                .map_err(|_| "couldn't serialize daily challenge history")?;
+            let mut file = File::create(DAILY_FILE)
+                .map_err(|_| "couldn't create save file for daily challenges")?;
+            file.write_all(serialized.as_bytes())
+                .map_err(|_| "couldn't write to save file")?;
+            Ok(())
+        }
+        if let Err(msg) = save_helper(self) {
+            println!("{}", msg);
+        }
+    }
+
+    pub fn result(&self, date: NaiveDate, profile: &str) -> Option<&DailyResult> {
+        let date = date.to_string();
+        self.results
+            .iter()
+            .find(|result| result.date == date && result.profile == profile)
+    }
+
+    /// Keeps the run if it's the best one that day, returns whether it was
+    pub fn record(&mut self, date: NaiveDate, profile: &str, score: u32, waves: usize) -> bool {
+        let date = date.to_string();
+        match self
+            .results
+            .iter_mut()
+            .find(|result| result.date == date && result.profile == profile)
+        {
+            Some(result) if score > result.score => {
+                result.score = score;
+                result.waves = waves;
+            }
+            Some(_) => return false,
+            None => self.results.push(DailyResult {
+                date,
+                profile: profile.to_string(),
+                score,
+                waves,
+            }),
+        }
+        true
+    }
+}
@@ -0,0 +1,263 @@
+//! A small web page a teacher can keep open to watch a class play. Started
+//! with `--dashboard [port]`, it answers plain HTTP on every interface:
+//!
+//! - `GET /` the dashboard, which refreshes itself from `/state.json`
+//! - `GET /state.json` the current session, e.g.
+//!   `{"player":"Ann","mode":"Campaign","state":"Playing","level":"Addition",
+//!   "wave":2,"waves":5,"points":1200,"lives":3,"correct":14,"wrong":2,
+//!   "accuracy":0.875,"struggling":[{"problem":"7 + 8","answer":"15","misses":2}]}`
+//!
+//! Everything the page needs is served from here, so it works without
+//! an internet connection.
+
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_DASHBOARD_PORT: u16 = 8080;
+// how often the server looks for new requests
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+/// How often the game hands over a new state, in ms. The page only asks
+/// every 2s, so there's no need to build one every frame
+pub const PUBLISH_INTERVAL: f32 = 500.0;
+/// How many misses before a problem shows up as one to work on
+const STRUGGLING_MISSES: u32 = 2;
+const MAX_STRUGGLING: usize = 8;
+
+/// A problem the player keeps getting wrong
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Fact {
+    pub problem: String,
+    pub answer: String,
+    pub misses: u32,
+}
+
+/// Right and wrong answers to each problem seen this session
+pub struct FactTally {
+    facts: Vec<(Fact, u32)>,
+}
+
+impl FactTally {
+    pub fn new() -> FactTally {
+        FactTally { facts: Vec::new() }
+    }
+
+    pub fn record(&mut self, problem: &str, answer: &str, correct: bool) {
+        let index = match self
+            .facts
+            .iter()
+            .position(|(fact, _)| fact.problem == problem)
+        {
+            Some(index) => index,
+            None => {
+                let fact = Fact {
+                    problem: problem.to_string(),
+                    answer: answer.to_string(),
+                    misses: 0,
+                };
+                self.facts.push((fact, 0));
+                self.facts.len() - 1
+            }
+        };
+        let (fact, hits) = &mut self.facts[index];
+        if correct {
+            *hits += 1;
+        } else {
+            fact.misses += 1;
+        }
+    }
+
+    /// Problems missed more than they've been answered, worst first
+    pub fn struggling(&self) -> Vec<Fact> {
+        let mut facts: Vec<Fact> = self
+            .facts
+            .iter()
+            .filter(|(fact, hits)| fact.misses >= STRUGGLING_MISSES && fact.misses > *hits)
+            .map(|(fact, _)| fact.clone())
+            .collect();
+        facts.sort_by_key(|fact| std::cmp::Reverse(fact.misses));
+        facts.truncate(MAX_STRUGGLING);
+        facts
+    }
+}
+
+/// What the dashboard shows, filled in by the game a few times a second
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct SessionState {
+    pub player: String,
+    pub mode: String,
+    /// Playing, Paused, In Menus and so on
+    pub state: String,
+    pub level: String,
+    /// Counting from 1
+    pub wave: usize,
+    pub waves: usize,
+    pub points: u32,
+    pub lives: usize,
+    pub correct: u32,
+    pub wrong: u32,
+    pub accuracy: f32,
+    pub struggling: Vec<Fact>,
+}
+
+/// Serves the latest session state until it's dropped
+pub struct Dashboard {
+    state: Arc<Mutex<SessionState>>,
+    running: Arc<AtomicBool>,
+}
+
+impl Dashboard {
+    pub fn start(port: u16) -> io::Result<Dashboard> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        // polled so the thread can notice the game going away
+        listener.set_nonblocking(true)?;
+        let state = Arc::new(Mutex::new(SessionState::default()));
+        let running = Arc::new(AtomicBool::new(true));
+        let shared = Arc::clone(&state);
+        let still_running = Arc::clone(&running);
+        thread::spawn(move || serve(listener, shared, still_running));
+        Ok(Dashboard { state, running })
+    }
+
+    pub fn publish(&self, state: SessionState) {
+        if let Ok(mut shared) = self.state.lock() {
+            *shared = state;
+        }
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+fn serve(listener: TcpListener, state: Arc<Mutex<SessionState>>, running: Arc<AtomicBool>) {
+    while running.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = respond(stream, &state) {
+                    println!("couldn't answer a dashboard request\n{}", e);
+                }
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+            Err(e) => println!("couldn't accept a dashboard request\n{}", e),
+        }
+    }
+}
+
+// only the request line matters, headers and bodies are ignored
+fn respond(stream: TcpStream, state: &Mutex<SessionState>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut request = String::new();
+    BufReader::new(&stream).read_line(&mut request)?;
+    let mut words = request.split_whitespace();
+    let method = words.next().unwrap_or("");
+    let path = words.next().unwrap_or("");
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/") | ("GET", "/index.html") => {
+            ("200 OK", "text/html; charset=utf-8", PAGE.to_string())
+        }
+        ("GET", "/state.json") => {
+            let state = match state.lock() {
+                Ok(state) => state.clone(),
+                Err(_) => SessionState::default(),
+            };
+            let json = serde_json::to_string(&state)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ("200 OK", "application/json", json)
+        }
+        ("GET", _) => ("404 Not Found", "text/plain", "not found".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "only GET is supported".to_string(),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    (&stream).write_all(response.as_bytes())
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Math Defense Dashboard</title>
+<style>
+body { font-family: sans-serif; background: #101522; color: #eee; margin: 2em; }
+h1 { font-weight: normal; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+td, th { padding: 0.3em 1em; text-align: left; border-bottom: 1px solid #333; }
+th { color: #999; font-weight: normal; }
+#status { color: #999; }
+.offline { color: #e66; }
+</style>
+</head>
+<body>
+<h1 id="player">Math Defense</h1>
+<p id="status">Connecting...</p>
+<table>
+<tr><th>Mode</th><td id="mode"></td></tr>
+<tr><th>Doing</th><td id="state"></td></tr>
+<tr><th>Level</th><td id="level"></td></tr>
+<tr><th>Wave</th><td id="wave"></td></tr>
+<tr><th>Points</th><td id="points"></td></tr>
+<tr><th>Guns</th><td id="lives"></td></tr>
+<tr><th>Accuracy</th><td id="accuracy"></td></tr>
+</table>
+<h2>Struggling With</h2>
+<table id="struggling"></table>
+<script>
+function set(id, text) { document.getElementById(id).textContent = text; }
+function row(table, cells, tag) {
+  var tr = table.insertRow();
+  cells.forEach(function (text) {
+    var cell = document.createElement(tag);
+    cell.textContent = text;
+    tr.appendChild(cell);
+  });
+}
+function refresh() {
+  fetch("/state.json").then(function (r) { return r.json(); }).then(function (s) {
+    set("player", s.player || "Math Defense");
+    set("mode", s.mode);
+    set("state", s.state);
+    set("level", s.level);
+    set("wave", s.waves ? s.wave + " of " + s.waves : s.wave);
+    set("points", s.points);
+    set("lives", s.lives);
+    var answers = s.correct + s.wrong;
+    set("accuracy", answers ? Math.round(s.accuracy * 100) + "% (" + s.correct + " of " + answers + ")" : "No answers yet");
+    var table = document.getElementById("struggling");
+    table.innerHTML = "";
+    if (s.struggling.length) {
+      row(table, ["Problem", "Answer", "Misses"], "th");
+      s.struggling.forEach(function (f) { row(table, [f.problem, f.answer, f.misses], "td"); });
+    } else {
+      row(table, ["Nothing yet"], "td");
+    }
+    set("status", "Updated " + new Date().toLocaleTimeString());
+    document.getElementById("status").className = "";
+  }).catch(function () {
+    set("status", "Can't reach the game");
+    document.getElementById("status").className = "offline";
+  });
+}
+refresh();
+setInterval(refresh, 2000);
+</script>
+</body>
+</html>
+"#;
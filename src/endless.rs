@@ -0,0 +1,69 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::level::*;
+
+// operations join the mix as the waves go on, (first wave, operation)
+const OPERATIONS: [(usize, Operation); 7] = [
+    (0, Operation::Add),
+    (2, Operation::Subtract),
+    (4, Operation::Multiply),
+    (6, Operation::Divide),
+    (9, Operation::Modulo),
+    (12, Operation::Power),
+    (15, Operation::SquareRoot),
+];
+// at most this many kinds of alien in one wave
+const MAX_GROUPS: usize = 3;
+const MAX_SPEED: f32 = 9.0;
+
+/// A level with just the first wave, the rest get added as they're reached
+pub fn endless_level<R: Rng>(rng: &mut R) -> Level {
+    Level {
+        waves: vec![endless_wave(0, rng)],
+        background_file: "/spacebg5.jpg".to_string(),
+        title: "Endless".to_string(),
+        unlocked: [true; 4],
+        music_file: None,
+        stars: [0; 4],
+        unlock_rules: Vec::new(),
+    }
+}
+
+/// Wave `number` counting from 0, each one faster and bigger than the last
+pub fn endless_wave<R: Rng>(number: usize, rng: &mut R) -> Wave {
+    let available: Vec<Operation> = OPERATIONS
+        .iter()
+        .filter(|(first_wave, _)| number >= *first_wave)
+        .map(|(_, operation)| *operation)
+        .collect();
+    let operations: Vec<Operation> = available
+        .choose_multiple(rng, MAX_GROUPS)
+        .cloned()
+        .collect();
+
+    let speed = (2.0 + 0.25 * number as f32).min(MAX_SPEED);
+    let num_ships = 4 + number;
+    let max_number = 5 + number as i32 / 3;
+    let groups = operations
+        .iter()
+        .enumerate()
+        .map(|(i, operation)| WaveGroup {
+            operation: *operation,
+            // the first group takes whatever doesn't divide evenly
+            num_ships: num_ships / operations.len()
+                + if i == 0 {
+                    num_ships % operations.len()
+                } else {
+                    0
+                },
+            speed: speed * rng.gen_range(0.8, 1.0),
+            min_number: 0,
+            max_number,
+            template_file: None,
+            kind: AlienKind::Normal,
+            pattern: Pattern::Straight,
+        })
+        .collect();
+    Wave { groups }
+}
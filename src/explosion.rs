@@ -1,9 +1,9 @@
-use ggez::audio::SoundSource;
 use ggez::graphics::{self, Color, DrawParam};
 use ggez::nalgebra as na;
 use ggez::Context;
 
 use crate::assets::*;
+use crate::audio::*;
 
 pub struct Explosion {
     pub start_time: f32, // millis
@@ -12,6 +12,8 @@ pub struct Explosion {
     pub index: usize,
     pub pos: na::Point2<f32>,
     pub sound_played: bool,
+    /// 1.0 draws the sprite sheet's 64 pixel frames as they are
+    pub scale: f32,
 }
 impl Explosion {
     pub fn new(start_time: f32, pos: na::Point2<f32>) -> Explosion {
@@ -22,6 +24,7 @@ impl Explosion {
             index: 0,
             pos: pos,
             sound_played: false,
+            scale: 1.0,
         }
     }
     pub fn get_rect(&self) -> graphics::Rect {
@@ -36,6 +39,11 @@ impl Explosion {
         )
     }
 
+    /// Whether it's been and gone
+    pub fn finished(&self) -> bool {
+        self.elapsed - self.start_time > self.duration
+    }
+
     pub fn update(&mut self, _ctx: &mut Context, dt: std::time::Duration) {
         if self.elapsed - self.start_time <= self.duration {
             self.elapsed += dt.as_millis() as f32;
@@ -52,18 +60,20 @@ impl Explosion {
     pub fn draw(&mut self, ctx: &mut Context, assets: &mut Assets) {
         if self.elapsed >= self.start_time {
             if !self.sound_played {
-                let _ = assets.laser_sound.play_detached();
-                let _ = assets.explosion_sound.play_detached();
+                assets.audio.play(Sound::Laser);
+                assets.audio.play(Sound::Explosion);
                 self.sound_played = true;
             }
             if self.elapsed - self.start_time <= self.duration {
                 let screen = graphics::size(ctx);
+                let half = 32.0 * self.scale;
                 let param = DrawParam::new()
                     .color(Color::from((255, 255, 255, 255)))
                     .dest(
                         na::Point2::new(self.pos[0] * screen.0, self.pos[1] * screen.1)
-                            - na::Vector2::new(32.0, 32.0),
+                            - na::Vector2::new(half, half),
                     )
+                    .scale(na::Vector2::new(self.scale, self.scale))
                     .src(self.get_rect());
                 let _ = graphics::draw(ctx, &assets.explosion, param);
             }
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+
+const HIGH_SCORES_FILE: &str = "high_scores.json";
+pub const TABLE_SIZE: usize = 10;
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+}
+
+/// The best scores for one level at one difficulty on one profile, highest
+/// first. Levels are matched by title so reordering levels.json keeps them.
+#[derive(Deserialize, Serialize)]
+pub struct HighScoreTable {
+    pub level: String,
+    pub difficulty: usize,
+    pub profile: String,
+    pub scores: Vec<HighScore>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct HighScores {
+    pub tables: Vec<HighScoreTable>,
+}
+
+impl HighScores {
+    pub fn load_from_file() -> HighScores {
+        //if any of this fails, start with empty tables
+        fn load_helper() -> Result<HighScores, String> {
+            let mut file =
+                File::open(HIGH_SCORES_FILE).map_err(|e| format!("file not found\n {}", e))?;
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)
+                .map_err(|e| format!("file could not be read\n{}", e))?;
+            let high_scores: HighScores = serde_json::from_slice(&buffer[..])
+                .map_err(|e| format!("file not valid\n{}", e))?;
+            Ok(high_scores)
+        }
+
+        match load_helper() {
+            Ok(high_scores) => high_scores,
+            Err(msg) => {
+                println!("Error loading high score file.\nStarting fresh\n{}", msg);
+                HighScores { tables: Vec::new() }
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        fn save_helper(high_scores: &HighScores) -> Result<(), String> {
+            let serialized = serde_json::to_string_pretty(high_scores)
+                .map_err(|_| "couldn't serialize high scores")?;
+            let mut file = File::create(HIGH_SCORES_FILE)
+                .map_err(|_| "couldn't create save file for high scores")?;
+            file.write_all(serialized.as_bytes())
+                .map_err(|_| "couldn't write to save file")?;
+            Ok(())
+        }
+        if let Err(msg) = save_helper(self) {
+            println!("{}", msg);
+        }
+    }
+
+    pub fn scores(&self, level: &str, difficulty: usize, profile: &str) -> &[HighScore] {
+        self.tables
+            .iter()
+            .find(|table| {
+                table.level == level && table.difficulty == difficulty && table.profile == profile
+            })
+            .map(|table| &table.scores[..])
+            .unwrap_or(&[])
+    }
+
+    /// Whether `score` would make it onto the table
+    pub fn qualifies(&self, level: &str, difficulty: usize, profile: &str, score: u32) -> bool {
+        let scores = self.scores(level, difficulty, profile);
+        score > 0 && (scores.len() < TABLE_SIZE || scores.iter().any(|entry| score > entry.score))
+    }
+
+    pub fn insert(&mut self, level: &str, difficulty: usize, profile: &str, entry: HighScore) {
+        let index = match self.tables.iter().position(|table| {
+            table.level == level && table.difficulty == difficulty && table.profile == profile
+        }) {
+            Some(index) => index,
+            None => {
+                self.tables.push(HighScoreTable {
+                    level: level.to_string(),
+                    difficulty,
+                    profile: profile.to_string(),
+                    scores: Vec::new(),
+                });
+                self.tables.len() - 1
+            }
+        };
+        let scores = &mut self.tables[index].scores;
+        // ties go below the scores that got there first
+        let rank = scores
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(scores.len());
+        scores.insert(rank, entry);
+        scores.truncate(TABLE_SIZE);
+    }
+}
@@ -0,0 +1,189 @@
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::nalgebra as na;
+use ggez::Context;
+
+use crate::assets::*;
+use crate::ggez_utility::*;
+
+const KEY_WIDTH: f32 = 0.045;
+const KEY_HEIGHT: f32 = 0.06;
+const KEY_GAP: f32 = 0.005;
+const COLUMNS: usize = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Key {
+    Char(char),
+    Back,
+    Enter,
+}
+
+/// A grid of number keys. Touchscreens get one in the bottom left corner,
+/// gamepads get a smaller one beside the turret with a selected key.
+pub struct Keypad {
+    pub keys: Vec<(Key, graphics::Text)>,
+    pub visible: bool,
+    pub selected: Option<usize>,
+    pub left: f32,
+    pub bottom: f32,
+    pub scale: f32,
+}
+
+/// Digits and '-' laid out like a number pad, then `extra` for any symbols
+/// the current wave needs, then enter
+pub fn number_keys(extra: &str) -> Vec<Key> {
+    let mut keys: Vec<Key> = "789456123-0".chars().map(Key::Char).collect();
+    keys.push(Key::Back);
+    keys.extend(extra.chars().map(Key::Char));
+    keys.push(Key::Enter);
+    keys
+}
+
+impl Keypad {
+    pub fn new(extra: &str, assets: &Assets) -> Keypad {
+        Keypad::with_keys(number_keys(extra), assets)
+    }
+
+    pub fn with_keys(keys: Vec<Key>, assets: &Assets) -> Keypad {
+        Keypad {
+            keys: keys
+                .into_iter()
+                .map(|key| {
+                    let label = match key {
+                        Key::Char(ch) => ch.to_string(),
+                        Key::Back => "DEL".to_string(),
+                        Key::Enter => "OK".to_string(),
+                    };
+                    (key, graphics::Text::new((label, assets.number_font, 32.0)))
+                })
+                .collect(),
+            visible: false,
+            selected: None,
+            left: 0.02,
+            bottom: 0.97,
+            scale: 1.0,
+        }
+    }
+
+    /// The gamepad picker, tucked in to the right of the turret
+    pub fn picker(keys: Vec<Key>, assets: &Assets) -> Keypad {
+        Keypad {
+            selected: Some(0),
+            left: 0.56,
+            bottom: 0.99,
+            scale: 0.6,
+            ..Keypad::with_keys(keys, assets)
+        }
+    }
+
+    fn rows(&self) -> usize {
+        // enter gets a row to itself
+        match self.keys.last() {
+            Some((Key::Enter, _)) => (self.keys.len() - 1 + COLUMNS - 1) / COLUMNS + 1,
+            _ => (self.keys.len() + COLUMNS - 1) / COLUMNS,
+        }
+    }
+
+    fn key_rect(&self, index: usize, screen: (f32, f32)) -> graphics::Rect {
+        let w = KEY_WIDTH * self.scale * screen.0;
+        let h = KEY_HEIGHT * self.scale * screen.1;
+        let gap = KEY_GAP * self.scale * screen.0;
+        let top = self.bottom * screen.1 - self.rows() as f32 * (h + gap);
+        let left = self.left * screen.0;
+        if self.keys[index].0 == Key::Enter {
+            graphics::Rect::new(
+                left,
+                top + (self.rows() - 1) as f32 * (h + gap),
+                COLUMNS as f32 * w + (COLUMNS - 1) as f32 * gap,
+                h,
+            )
+        } else {
+            graphics::Rect::new(
+                left + (index % COLUMNS) as f32 * (w + gap),
+                top + (index / COLUMNS) as f32 * (h + gap),
+                w,
+                h,
+            )
+        }
+    }
+
+    /// The key under a click or tap, in pixels
+    pub fn key_at(&self, pos: na::Point2<f32>, screen: (f32, f32)) -> Option<Key> {
+        if !self.visible {
+            return None;
+        }
+        (0..self.keys.len())
+            .find(|i| self.key_rect(*i, screen).contains(pos))
+            .map(|i| self.keys[i].0)
+    }
+
+    pub fn selected_key(&self) -> Option<Key> {
+        self.selected.map(|i| self.keys[i].0)
+    }
+
+    /// Moves the selection to the nearest key in the direction of (dx, dy)
+    pub fn move_selection(&mut self, dx: f32, dy: f32) {
+        let current = match self.selected {
+            Some(current) => current,
+            None => {
+                self.selected = Some(0);
+                return;
+            }
+        };
+        let center = |rect: graphics::Rect| (rect.x + rect.w / 2.0, rect.y + rect.h / 2.0);
+        let (cx, cy) = center(self.key_rect(current, (1.0, 1.0)));
+        let next = (0..self.keys.len())
+            .filter(|i| *i != current)
+            .map(|i| {
+                let (x, y) = center(self.key_rect(i, (1.0, 1.0)));
+                (i, x - cx, y - cy)
+            })
+            // only keys that are actually that way
+            .filter(|(_, x, y)| x * dx + y * dy > 0.0001)
+            .min_by(|(_, x1, y1), (_, x2, y2)| {
+                (x1 * x1 + y1 * y1)
+                    .partial_cmp(&(x2 * x2 + y2 * y2))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        if let Some((i, _, _)) = next {
+            self.selected = Some(i);
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context) {
+        if !self.visible {
+            return;
+        }
+        let screen = graphics::size(ctx);
+        for (i, (_, label)) in self.keys.iter().enumerate() {
+            let rect = self.key_rect(i, screen);
+            let selected = self.selected == Some(i);
+            let fill = if selected {
+                Color::new(0.75, 0.0, 0.25, 0.6)
+            } else {
+                Color::new(0.0, 0.0, 0.0, 0.6)
+            };
+            if let Ok(background) =
+                graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, fill)
+            {
+                let _ = graphics::draw(ctx, &background, DrawParam::default());
+            }
+            let border = if selected { WHITE } else { GRAY };
+            if let Ok(border) =
+                graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.0), rect, border)
+            {
+                let _ = graphics::draw(ctx, &border, DrawParam::default());
+            }
+            let (tw, th) = label.dimensions(ctx);
+            let (tw, th) = (tw as f32 * self.scale, th as f32 * self.scale);
+            let dest = na::Point2::new(rect.x + (rect.w - tw) / 2.0, rect.y + (rect.h - th) / 2.0);
+            let _ = graphics::draw(
+                ctx,
+                label,
+                DrawParam::new()
+                    .color(WHITE)
+                    .dest(dest)
+                    .scale(na::Vector2::new(self.scale, self.scale)),
+            );
+        }
+    }
+}
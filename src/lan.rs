@@ -0,0 +1,597 @@
+//! Matches between machines on the same network. Everyone plays the same
+//! seeded waves and sees everyone else's progress as it happens.
+//!
+//! The protocol is one JSON object per line over TCP, tagged by `type`:
+//!
+//! - `{"type":"join","version":2,"name":"Ann"}` a player arriving. Clients
+//!   send it first, the host then sends one for each player already there.
+//! - `{"type":"welcome","name":"Ann 2"}` the host's answer to a join, with
+//!   the name the player is listed under. It's the name they asked for
+//!   unless someone already has it.
+//! - `{"type":"seed","version":2,"seed":42,"difficulty":1}` the host starting
+//!   a match, sent again to anyone who joins part way through.
+//! - `{"type":"progress","name":"Ann","wave":2}` waves cleared so far.
+//! - `{"type":"score","name":"Ann","score":1200}` points so far.
+//! - `{"type":"finish","name":"Ann","score":1500,"waves":5}` a run that's over.
+//!
+//! Clients only talk to the host, which passes progress, score and finish
+//! messages on to everyone else under the name it gave that connection, so
+//! two machines with the same profile name never share a row. A join or
+//! seed with a different `version` ends the connection.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::daily::*;
+use crate::level::*;
+
+pub const PROTOCOL_VERSION: u32 = 2;
+pub const DEFAULT_PORT: u16 = 7878;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+// how often the host looks for new players
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LanMessage {
+    Join {
+        version: u32,
+        name: String,
+    },
+    Welcome {
+        name: String,
+    },
+    Seed {
+        version: u32,
+        seed: u64,
+        difficulty: usize,
+    },
+    Progress {
+        name: String,
+        wave: usize,
+    },
+    Score {
+        name: String,
+        score: u32,
+    },
+    Finish {
+        name: String,
+        score: u32,
+        waves: usize,
+    },
+}
+
+impl LanMessage {
+    /// The same message about the player called `name`
+    fn renamed(&self, name: &str) -> LanMessage {
+        let mut message = self.clone();
+        match &mut message {
+            LanMessage::Join { name: old, .. }
+            | LanMessage::Progress { name: old, .. }
+            | LanMessage::Score { name: old, .. }
+            | LanMessage::Finish { name: old, .. } => *old = name.to_string(),
+            LanMessage::Welcome { .. } | LanMessage::Seed { .. } => (),
+        }
+        message
+    }
+}
+
+/// The level every machine in a match builds from the host's seed
+pub fn lan_level(seed: u64) -> Level {
+    Level {
+        waves: seeded_waves(seed),
+        background_file: "/spacebg2.jpg".to_string(),
+        title: "LAN Match".to_string(),
+        unlocked: [true; 4],
+        music_file: None,
+        stars: [0; 4],
+        unlock_rules: Vec::new(),
+    }
+}
+
+fn send_line(mut stream: &TcpStream, message: &LanMessage) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+// reads lines until the connection closes or nobody's listening any more,
+// lines that aren't messages are skipped
+fn read_messages<F: Fn(LanMessage) -> bool>(stream: TcpStream, deliver: F) {
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        match serde_json::from_str::<LanMessage>(&line) {
+            Ok(message) => {
+                if !deliver(message) {
+                    return;
+                }
+            }
+            Err(e) => println!("skipping lan message {:?}\n{}", line, e),
+        }
+    }
+}
+
+/// Accepts players and keeps a connection to each of them
+pub struct LanHost {
+    clients: Arc<Mutex<Vec<(usize, TcpStream)>>>,
+    incoming: Receiver<(usize, LanMessage)>,
+    running: Arc<AtomicBool>,
+}
+
+impl LanHost {
+    pub fn start(port: u16) -> io::Result<LanHost> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        // polled so the thread can notice the host going away
+        listener.set_nonblocking(true)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let running = Arc::new(AtomicBool::new(true));
+        let (sender, incoming) = mpsc::channel();
+        let accepted = Arc::clone(&clients);
+        let still_running = Arc::clone(&running);
+        thread::spawn(move || accept_loop(listener, accepted, sender, still_running));
+        Ok(LanHost {
+            clients,
+            incoming,
+            running,
+        })
+    }
+
+    pub fn poll(&self) -> Vec<(usize, LanMessage)> {
+        self.incoming.try_iter().collect()
+    }
+
+    pub fn send_to(&self, client: usize, message: &LanMessage) {
+        self.send_where(message, |id| id == client);
+    }
+
+    pub fn broadcast(&self, message: &LanMessage) {
+        self.send_where(message, |_| true);
+    }
+
+    /// To everyone but the player it came from
+    pub fn relay(&self, from: usize, message: &LanMessage) {
+        self.send_where(message, |id| id != from);
+    }
+
+    // anyone who can't be written to has gone, so they're dropped
+    fn send_where<F: Fn(usize) -> bool>(&self, message: &LanMessage, to: F) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain(|(id, stream)| !to(*id) || send_line(stream, message).is_ok());
+        }
+    }
+
+    pub fn disconnect(&self, client: usize) {
+        if let Ok(mut clients) = self.clients.lock() {
+            for (_, stream) in clients.iter().filter(|(id, _)| *id == client) {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+            clients.retain(|(id, _)| *id != client);
+        }
+    }
+}
+
+impl Drop for LanHost {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Ok(clients) = self.clients.lock() {
+            for (_, stream) in clients.iter() {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+fn accept_loop(
+    listener: TcpListener,
+    clients: Arc<Mutex<Vec<(usize, TcpStream)>>>,
+    sender: Sender<(usize, LanMessage)>,
+    running: Arc<AtomicBool>,
+) {
+    let mut next_id = 0;
+    while running.load(Ordering::SeqCst) {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_INTERVAL);
+                continue;
+            }
+            Err(e) => {
+                println!("couldn't accept a lan player\n{}", e);
+                continue;
+            }
+        };
+        let id = next_id;
+        next_id += 1;
+        let writer = match stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.try_clone())
+        {
+            Ok(writer) => writer,
+            Err(_) => continue,
+        };
+        if let Ok(mut clients) = clients.lock() {
+            clients.push((id, writer));
+        }
+        let sender = sender.clone();
+        thread::spawn(move || read_messages(stream, |message| sender.send((id, message)).is_ok()));
+    }
+}
+
+/// A connection to someone else's match
+pub struct LanClient {
+    stream: TcpStream,
+    incoming: Receiver<LanMessage>,
+    connected: bool,
+}
+
+impl LanClient {
+    /// `address` is a host name or ip, with the default port if it doesn't
+    /// give one
+    pub fn connect(address: &str, name: &str) -> io::Result<LanClient> {
+        let address = if address.contains(':') {
+            address.to_string()
+        } else {
+            format!("{}:{}", address, DEFAULT_PORT)
+        };
+        let socket = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "couldn't find that address"))?;
+        let stream = TcpStream::connect_timeout(&socket, CONNECT_TIMEOUT)?;
+        let reader = stream.try_clone()?;
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || read_messages(reader, |message| sender.send(message).is_ok()));
+        let join = LanMessage::Join {
+            version: PROTOCOL_VERSION,
+            name: name.to_string(),
+        };
+        send_line(&stream, &join)?;
+        Ok(LanClient {
+            stream,
+            incoming,
+            connected: true,
+        })
+    }
+
+    pub fn poll(&mut self) -> Vec<LanMessage> {
+        let mut messages = Vec::new();
+        loop {
+            match self.incoming.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+        messages
+    }
+
+    pub fn send(&mut self, message: &LanMessage) {
+        if send_line(&self.stream, message).is_err() {
+            self.connected = false;
+        }
+    }
+
+    pub fn connected(&self) -> bool {
+        self.connected
+    }
+}
+
+impl Drop for LanClient {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// How one player is doing in the current match
+#[derive(Debug, Clone)]
+pub struct LanEntry {
+    pub name: String,
+    pub wave: usize,
+    pub score: u32,
+    pub finished: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Leaderboard {
+    pub entries: Vec<LanEntry>,
+}
+
+impl Leaderboard {
+    pub fn new() -> Leaderboard {
+        Leaderboard {
+            entries: Vec::new(),
+        }
+    }
+
+    fn entry(&mut self, name: &str) -> &mut LanEntry {
+        let index = match self.entries.iter().position(|entry| entry.name == name) {
+            Some(index) => index,
+            None => {
+                self.entries.push(LanEntry {
+                    name: name.to_string(),
+                    wave: 0,
+                    score: 0,
+                    finished: false,
+                });
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[index]
+    }
+
+    pub fn apply(&mut self, message: &LanMessage) {
+        match message {
+            LanMessage::Join { name, .. } => {
+                self.entry(name);
+            }
+            LanMessage::Welcome { .. } | LanMessage::Seed { .. } => (),
+            LanMessage::Progress { name, wave } => self.entry(name).wave = *wave,
+            LanMessage::Score { name, score } => self.entry(name).score = *score,
+            LanMessage::Finish { name, score, waves } => {
+                let entry = self.entry(name);
+                entry.score = *score;
+                entry.wave = *waves;
+                entry.finished = true;
+            }
+        }
+    }
+
+    /// `name`, or `name` with the first number after it nobody has yet
+    pub fn unique_name(&self, name: &str) -> String {
+        let taken = |name: &str| self.entries.iter().any(|entry| entry.name == name);
+        let mut unique = name.to_string();
+        let mut number = 2;
+        while taken(&unique) {
+            unique = format!("{} {}", name, number);
+            number += 1;
+        }
+        unique
+    }
+
+    fn rename(&mut self, from: &str, to: &str) {
+        for entry in self.entries.iter_mut().filter(|entry| entry.name == from) {
+            entry.name = to.to_string();
+        }
+    }
+
+    /// Everyone back to the start of a new match
+    pub fn reset(&mut self) {
+        for entry in &mut self.entries {
+            entry.wave = 0;
+            entry.score = 0;
+            entry.finished = false;
+        }
+    }
+
+    /// Highest score first
+    pub fn ranked(&self) -> Vec<&LanEntry> {
+        let mut ranked: Vec<&LanEntry> = self.entries.iter().collect();
+        ranked.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        ranked
+    }
+}
+
+pub enum Link {
+    Host(LanHost),
+    Client(LanClient),
+}
+
+/// This machine's part in a match, as host or as one of the players
+pub struct LanSession {
+    pub link: Link,
+    pub name: String,
+    pub leaderboard: Leaderboard,
+    /// The match being played, once the host has started one
+    pub seed: Option<u64>,
+    pub difficulty: usize,
+    /// The host has just started a match this client hasn't picked up yet
+    pub new_match: bool,
+    // the name the host gave each connection that's joined
+    players: Vec<(usize, String)>,
+}
+
+impl LanSession {
+    pub fn host(port: u16, name: &str) -> io::Result<LanSession> {
+        Ok(LanSession::new(Link::Host(LanHost::start(port)?), name))
+    }
+
+    pub fn join(address: &str, name: &str) -> io::Result<LanSession> {
+        Ok(LanSession::new(
+            Link::Client(LanClient::connect(address, name)?),
+            name,
+        ))
+    }
+
+    fn new(link: Link, name: &str) -> LanSession {
+        let mut leaderboard = Leaderboard::new();
+        leaderboard.apply(&LanMessage::Join {
+            version: PROTOCOL_VERSION,
+            name: name.to_string(),
+        });
+        LanSession {
+            link,
+            name: name.to_string(),
+            leaderboard,
+            seed: None,
+            difficulty: 0,
+            new_match: false,
+            players: Vec::new(),
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        matches!(self.link, Link::Host(_))
+    }
+
+    pub fn connected(&self) -> bool {
+        match &self.link {
+            Link::Host(_) => true,
+            Link::Client(client) => client.connected(),
+        }
+    }
+
+    /// Only the host can start a match, everyone else hears about it
+    pub fn start_match(&mut self, seed: u64, difficulty: usize) {
+        if let Link::Host(host) = &self.link {
+            self.seed = Some(seed);
+            self.difficulty = difficulty;
+            self.leaderboard.reset();
+            host.broadcast(&LanMessage::Seed {
+                version: PROTOCOL_VERSION,
+                seed,
+                difficulty,
+            });
+        }
+    }
+
+    /// This machine's own progress, kept here and sent to everyone else
+    pub fn report(&mut self, message: LanMessage) {
+        self.leaderboard.apply(&message);
+        match &mut self.link {
+            Link::Host(host) => host.broadcast(&message),
+            Link::Client(client) => client.send(&message),
+        }
+    }
+
+    /// Handles whatever's arrived since the last frame
+    pub fn update(&mut self) {
+        match &mut self.link {
+            Link::Host(host) => {
+                for (id, message) in host.poll() {
+                    match &message {
+                        LanMessage::Join { version, .. } if *version != PROTOCOL_VERSION => {
+                            host.disconnect(id)
+                        }
+                        LanMessage::Join { name, .. } => {
+                            let name = self.leaderboard.unique_name(name);
+                            host.send_to(id, &LanMessage::Welcome { name: name.clone() });
+                            self.players.push((id, name.clone()));
+                            let message = message.renamed(&name);
+                            // the new player hears who's here and what's being played
+                            for entry in &self.leaderboard.entries {
+                                let join = LanMessage::Join {
+                                    version: PROTOCOL_VERSION,
+                                    name: entry.name.clone(),
+                                };
+                                host.send_to(id, &join);
+                            }
+                            if let Some(seed) = self.seed {
+                                let seed = LanMessage::Seed {
+                                    version: PROTOCOL_VERSION,
+                                    seed,
+                                    difficulty: self.difficulty,
+                                };
+                                host.send_to(id, &seed);
+                            }
+                            host.relay(id, &message);
+                            self.leaderboard.apply(&message);
+                        }
+                        // only the host starts matches and names players
+                        LanMessage::Seed { .. } | LanMessage::Welcome { .. } => (),
+                        // passed on under the name this connection joined as,
+                        // whatever name the message itself gives
+                        _ => {
+                            let player = self.players.iter().find(|(player, _)| *player == id);
+                            if let Some((_, name)) = player {
+                                let message = message.renamed(name);
+                                host.relay(id, &message);
+                                self.leaderboard.apply(&message);
+                            }
+                        }
+                    }
+                }
+            }
+            Link::Client(client) => {
+                for message in client.poll() {
+                    match message {
+                        LanMessage::Seed {
+                            version,
+                            seed,
+                            difficulty,
+                        } if version == PROTOCOL_VERSION => {
+                            self.seed = Some(seed);
+                            self.difficulty = difficulty;
+                            self.leaderboard.reset();
+                            self.new_match = true;
+                        }
+                        LanMessage::Seed { version, .. } => {
+                            println!(
+                                "the host speaks lan version {}, this is version {}",
+                                version, PROTOCOL_VERSION
+                            );
+                            let _ = client.stream.shutdown(Shutdown::Both);
+                        }
+                        LanMessage::Welcome { name } => {
+                            self.leaderboard.rename(&self.name, &name);
+                            self.name = name;
+                        }
+                        message => self.leaderboard.apply(&message),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A stand in player with no window, for trying out matches on one machine.
+/// Joins `address`, then clears a wave every few seconds of each match the
+/// host starts, printing the leaderboard as it goes.
+pub fn run_bot(address: &str, name: &str) -> io::Result<()> {
+    let mut session = LanSession::join(address, name)?;
+    println!("{} joined {}", name, address);
+    let mut rng = rand::thread_rng();
+    while session.connected() {
+        thread::sleep(Duration::from_millis(100));
+        session.update();
+        if !session.new_match {
+            continue;
+        }
+        session.new_match = false;
+        println!("match started with seed {:?}", session.seed);
+        let mut score = 0;
+        for wave in 1..=DAILY_WAVES {
+            thread::sleep(Duration::from_secs(3));
+            session.update();
+            score += rng.gen_range(50, 150);
+            session.report(LanMessage::Progress {
+                name: session.name.clone(),
+                wave,
+            });
+            session.report(LanMessage::Score {
+                name: session.name.clone(),
+                score,
+            });
+        }
+        session.report(LanMessage::Finish {
+            name: session.name.clone(),
+            score,
+            waves: DAILY_WAVES,
+        });
+        for (i, entry) in session.leaderboard.ranked().iter().enumerate() {
+            println!(
+                "{}. {} {} wave {}{}",
+                i + 1,
+                entry.name,
+                entry.score,
+                entry.wave,
+                if entry.finished { " done" } else { "" }
+            );
+        }
+    }
+    println!("the host has gone");
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::assets::*;
+use crate::audio::*;
 use crate::message::*;
 use ggez::Context;
 
@@ -14,6 +15,51 @@ pub enum Operation {
     Subtract,
     Multiply,
     Divide,
+    Power,
+    SquareRoot,
+    Modulo,
+    PercentOf,
+    Compare,
+    RoundTen,
+    RoundHundred,
+    WordProblem,
+    MoneySum,
+    MakeChange,
+    TimeAfter,
+    TimeElapsed,
+}
+
+impl Operation {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operation::Add => "+",
+            Operation::Subtract => "-",
+            Operation::Multiply => "X",
+            Operation::Divide => "/",
+            Operation::Power => "^",
+            Operation::SquareRoot => "√",
+            Operation::Modulo => " mod ",
+            Operation::PercentOf => "% of ",
+            Operation::Compare => " □ ",
+            Operation::RoundTen => " → 10s",
+            Operation::RoundHundred => " → 100s",
+            Operation::WordProblem => "?",
+            Operation::MoneySum => "+",
+            Operation::MakeChange => " - ",
+            Operation::TimeAfter => " + ",
+            Operation::TimeElapsed => " → ",
+        }
+    }
+
+    /// Characters besides digits and '-' that answers to this operation use
+    pub fn extra_input(&self) -> &'static str {
+        match self {
+            Operation::Compare => "<>=",
+            Operation::MoneySum | Operation::MakeChange => "$.",
+            Operation::TimeAfter => ":",
+            _ => "",
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -22,6 +68,40 @@ pub struct Level {
     pub background_file: String,
     pub title: String,
     pub unlocked: [bool; 4],
+    // a track in resources, the default music plays when there isn't one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub music_file: Option<String>,
+    /// Best star rating so far at each difficulty, 0 until cleared
+    #[serde(default)]
+    pub stars: [u32; 4],
+    // when there are rules they decide the unlock instead of clearing the level before
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unlock_rules: Vec<UnlockRule>,
+}
+
+/// A requirement for unlocking a level, checked at the difficulty being played.
+/// In levels.json, e.g. `{"LevelStars": {"level": "Division Disaster!", "stars": 2}}`
+/// or `{"TotalStars": 10}`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum UnlockRule {
+    /// At least `stars` on the level with this title
+    LevelStars { level: String, stars: u32 },
+    /// At least this many stars across every level
+    TotalStars(u32),
+}
+
+impl UnlockRule {
+    fn met(&self, levels: &[Level], difficulty: usize) -> bool {
+        match self {
+            UnlockRule::LevelStars { level, stars } => levels
+                .iter()
+                .any(|other| other.title == *level && other.stars[difficulty] >= *stars),
+            UnlockRule::TotalStars(stars) => {
+                let total: u32 = levels.iter().map(|level| level.stars[difficulty]).sum();
+                total >= *stars
+            }
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -29,6 +109,103 @@ pub struct Wave {
     pub groups: Vec<WaveGroup>,
 }
 
+/// What the ships in a group are. In levels.json a boss group has
+/// `"kind": {"Boss": {"hits": 5}}`, groups without a kind are ordinary aliens.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Default)]
+pub enum AlienKind {
+    #[default]
+    Normal,
+    /// Bigger, sways across the screen and takes `hits` right answers, each
+    /// to a different problem, to destroy
+    Boss { hits: usize },
+}
+
+impl AlienKind {
+    pub fn is_normal(&self) -> bool {
+        *self == AlienKind::Normal
+    }
+
+    /// Right answers it takes to destroy one
+    pub fn hits(&self) -> usize {
+        match self {
+            AlienKind::Normal => 1,
+            AlienKind::Boss { hits } => (*hits).max(1),
+        }
+    }
+}
+
+// how long one back and forth takes, in millis
+const ZIGZAG_PERIOD: f32 = 3000.0;
+const SWAY_PERIOD: f32 = 6000.0;
+const FORMATION_PERIOD: f32 = 8000.0;
+// divers drift in slowly until they're this far down
+const DIVE_HEIGHT: f32 = 0.4;
+// pause and drop aliens take turns dropping and hovering this long
+const PAUSE_TIME: f32 = 1000.0;
+
+/// How the ships in a group make their way down. In levels.json, e.g.
+/// `"pattern": "Zigzag"`, groups without a pattern fall straight down.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Default)]
+pub enum Pattern {
+    #[default]
+    Straight,
+    /// Sharp turns from side to side
+    Zigzag,
+    /// Smooth swings from side to side
+    Sway,
+    /// Drifts in slowly then dives
+    Dive,
+    /// Drops a way, hovers, drops again
+    PauseAndDrop,
+    /// Rows that march from side to side together
+    Formation,
+}
+
+// goes 0 to 1, back through 0 to -1 and back to 0 as `t` goes from 0 to 1
+fn triangle(t: f32) -> f32 {
+    1.0 - 4.0 * ((t + 0.25).fract() - 0.5).abs()
+}
+
+impl Pattern {
+    pub fn is_straight(&self) -> bool {
+        *self == Pattern::Straight
+    }
+
+    /// How far to either side of where it came in it can end up
+    pub fn reach(&self) -> f32 {
+        match self {
+            Pattern::Zigzag => 0.06,
+            Pattern::Sway => 0.12,
+            Pattern::Formation => 0.08,
+            _ => 0.0,
+        }
+    }
+
+    /// Where it is across the screen `age` millis in, relative to where it
+    /// came in
+    pub fn drift(&self, age: f32) -> f32 {
+        let reach = self.reach();
+        match self {
+            Pattern::Zigzag => reach * triangle(age / ZIGZAG_PERIOD),
+            Pattern::Sway => reach * (age / SWAY_PERIOD * std::f32::consts::TAU).sin(),
+            Pattern::Formation => reach * triangle(age / FORMATION_PERIOD),
+            _ => 0.0,
+        }
+    }
+
+    /// How many times its speed it falls at `height`, `age` millis in. Works
+    /// out to about the same time to reach the bottom as falling straight.
+    pub fn fall(&self, age: f32, height: f32) -> f32 {
+        match self {
+            Pattern::Dive if height < DIVE_HEIGHT => 0.5,
+            Pattern::Dive => 3.0,
+            Pattern::PauseAndDrop if (age / PAUSE_TIME) as u32 % 2 == 0 => 2.0,
+            Pattern::PauseAndDrop => 0.0,
+            _ => 1.0,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct WaveGroup {
     pub operation: Operation,
@@ -36,6 +213,37 @@ pub struct WaveGroup {
     pub num_ships: usize,
     pub max_number: i32,
     pub min_number: i32,
+    // only used by WordProblem groups, defaults to resources/word_problems.json
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_file: Option<String>,
+    #[serde(default, skip_serializing_if = "AlienKind::is_normal")]
+    pub kind: AlienKind,
+    #[serde(default, skip_serializing_if = "Pattern::is_straight")]
+    pub pattern: Pattern,
+}
+
+impl Wave {
+    /// Characters besides digits and '-' that answers in this wave use
+    pub fn extra_input(&self) -> String {
+        let mut extra = String::new();
+        for group in &self.groups {
+            for ch in group.operation.extra_input().chars() {
+                if !extra.contains(ch) {
+                    extra.push(ch);
+                }
+            }
+        }
+        extra
+    }
+
+    pub fn accepts_input(&self, ch: char) -> bool {
+        ch.is_ascii_digit()
+            || ch == '-'
+            || self
+                .groups
+                .iter()
+                .any(|group| group.operation.extra_input().contains(ch))
+    }
 }
 
 pub const DIFFICULTY_NAMES: [&str; 4] = ["Rookie", "Cadet", "Veteran", "Space Marine"];
@@ -45,6 +253,36 @@ pub const MIN_NUMBER_DIFFICULTY: [f32; 4] = [1.0, 1.25, 2.0, 3.0];
 pub const NUM_SHIPS_DIFFICULTY: [f32; 4] = [1.0, 1.25, 2.0, 3.0];
 
 impl Level {
+    pub fn music(&self) -> &str {
+        self.music_file.as_deref().unwrap_or(DEFAULT_MUSIC_FILE)
+    }
+
+    /// Records a clear of level `index`, then unlocks whatever that opens up
+    pub fn complete(levels: &mut [Level], index: usize, difficulty: usize, stars: u32) {
+        let best = &mut levels[index].stars[difficulty];
+        *best = (*best).max(stars);
+        if let Some(next) = levels.get_mut(index + 1) {
+            if next.unlock_rules.is_empty() {
+                next.unlocked[difficulty] = true;
+            }
+        }
+        let met: Vec<bool> = levels
+            .iter()
+            .map(|level| {
+                !level.unlock_rules.is_empty()
+                    && level
+                        .unlock_rules
+                        .iter()
+                        .all(|rule| rule.met(levels, difficulty))
+            })
+            .collect();
+        for (level, met) in levels.iter_mut().zip(met) {
+            if met {
+                level.unlocked[difficulty] = true;
+            }
+        }
+    }
+
     pub fn push_title(&self, messages: &mut VecDeque<Message>, assets: &Assets, ctx: &mut Context) {
         messages.push_back(Message::new(self.title.clone(), 2000.0, assets, ctx));
     }
@@ -96,6 +334,9 @@ impl Level {
                 unlocked: [true, true, true, true],
                 title: "Addition Attack!".to_string(),
                 background_file: "/spacebg1.jpg".to_string(),
+                music_file: None,
+                stars: [0; 4],
+                unlock_rules: Vec::new(),
                 waves: vec![
                     Wave {
                         groups: vec![WaveGroup {
@@ -104,6 +345,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Add,
                             num_ships: 5,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Straight,
                         }],
                     },
                     Wave {
@@ -113,6 +357,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Add,
                             num_ships: 8,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Straight,
                         }],
                     },
                     Wave {
@@ -122,6 +369,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Add,
                             num_ships: 10,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Straight,
                         }],
                     },
                 ],
@@ -131,6 +381,9 @@ impl Level {
                 unlocked: [false, false, false, false],
                 title: "Subtraction Subterfuge!".to_string(),
                 background_file: "/spacebg2.jpg".to_string(),
+                music_file: None,
+                stars: [0; 4],
+                unlock_rules: Vec::new(),
                 waves: vec![
                     Wave {
                         groups: vec![WaveGroup {
@@ -139,6 +392,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Subtract,
                             num_ships: 5,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Straight,
                         }],
                     },
                     Wave {
@@ -148,6 +404,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Subtract,
                             num_ships: 8,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Straight,
                         }],
                     },
                     Wave {
@@ -157,6 +416,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Subtract,
                             num_ships: 10,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Straight,
                         }],
                     },
                 ],
@@ -166,6 +428,9 @@ impl Level {
                 unlocked: [false, false, false, false],
                 title: "Multiplication Mayhem!".to_string(),
                 background_file: "/spacebg3.jpg".to_string(),
+                music_file: None,
+                stars: [0; 4],
+                unlock_rules: Vec::new(),
                 waves: vec![
                     Wave {
                         groups: vec![WaveGroup {
@@ -174,6 +439,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Multiply,
                             num_ships: 5,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Straight,
                         }],
                     },
                     Wave {
@@ -183,6 +451,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Multiply,
                             num_ships: 8,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Sway,
                         }],
                     },
                     Wave {
@@ -192,6 +463,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Multiply,
                             num_ships: 10,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Zigzag,
                         }],
                     },
                 ],
@@ -201,6 +475,9 @@ impl Level {
                 unlocked: [false, false, false, false],
                 title: "Division Disaster!".to_string(),
                 background_file: "/spacebg4.jpg".to_string(),
+                music_file: None,
+                stars: [0; 4],
+                unlock_rules: Vec::new(),
                 waves: vec![
                     Wave {
                         groups: vec![WaveGroup {
@@ -209,6 +486,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Divide,
                             num_ships: 5,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Straight,
                         }],
                     },
                     Wave {
@@ -218,6 +498,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Divide,
                             num_ships: 8,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::PauseAndDrop,
                         }],
                     },
                     Wave {
@@ -227,6 +510,9 @@ impl Level {
                             min_number: 0,
                             operation: Operation::Divide,
                             num_ships: 10,
+                            template_file: None,
+                            kind: AlienKind::Normal,
+                            pattern: Pattern::Dive,
                         }],
                     },
                 ],
@@ -236,6 +522,9 @@ impl Level {
                 unlocked: [false, false, false, false],
                 title: "The Final Assault!".to_string(),
                 background_file: "/spacebg5.jpg".to_string(),
+                music_file: None,
+                stars: [0; 4],
+                unlock_rules: Vec::new(),
                 waves: vec![
                     Wave {
                         groups: vec![
@@ -245,6 +534,9 @@ impl Level {
                                 min_number: 0,
                                 operation: Operation::Add,
                                 num_ships: 5,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Straight,
                             },
                             WaveGroup {
                                 speed: 2.5,
@@ -252,6 +544,9 @@ impl Level {
                                 min_number: 0,
                                 operation: Operation::Subtract,
                                 num_ships: 5,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Straight,
                             },
                         ],
                     },
@@ -263,6 +558,9 @@ impl Level {
                                 min_number: 0,
                                 operation: Operation::Add,
                                 num_ships: 3,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Formation,
                             },
                             WaveGroup {
                                 speed: 2.5,
@@ -270,6 +568,9 @@ impl Level {
                                 min_number: 0,
                                 operation: Operation::Subtract,
                                 num_ships: 3,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Straight,
                             },
                             WaveGroup {
                                 speed: 2.5,
@@ -277,6 +578,9 @@ impl Level {
                                 min_number: 0,
                                 operation: Operation::Multiply,
                                 num_ships: 3,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Straight,
                             },
                         ],
                     },
@@ -288,6 +592,9 @@ impl Level {
                                 min_number: 0,
                                 operation: Operation::Add,
                                 num_ships: 3,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Straight,
                             },
                             WaveGroup {
                                 speed: 3.5,
@@ -295,6 +602,9 @@ impl Level {
                                 min_number: 0,
                                 operation: Operation::Subtract,
                                 num_ships: 3,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Zigzag,
                             },
                             WaveGroup {
                                 speed: 2.5,
@@ -302,6 +612,9 @@ impl Level {
                                 min_number: 0,
                                 operation: Operation::Multiply,
                                 num_ships: 3,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Straight,
                             },
                             WaveGroup {
                                 speed: 1.5,
@@ -309,6 +622,34 @@ impl Level {
                                 min_number: 0,
                                 operation: Operation::Divide,
                                 num_ships: 3,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Straight,
+                            },
+                        ],
+                    },
+                    // the mothership, with a few escorts
+                    Wave {
+                        groups: vec![
+                            WaveGroup {
+                                speed: 1.0,
+                                max_number: 5,
+                                min_number: 0,
+                                operation: Operation::Multiply,
+                                num_ships: 1,
+                                template_file: None,
+                                kind: AlienKind::Boss { hits: 6 },
+                                pattern: Pattern::Straight,
+                            },
+                            WaveGroup {
+                                speed: 2.5,
+                                max_number: 5,
+                                min_number: 0,
+                                operation: Operation::Add,
+                                num_ships: 3,
+                                template_file: None,
+                                kind: AlienKind::Normal,
+                                pattern: Pattern::Formation,
                             },
                         ],
                     },
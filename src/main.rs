@@ -1,9 +1,9 @@
 #![windows_subsystem = "windows"]
 
+use chrono::{Datelike, NaiveDate};
 use ggez;
-use ggez::audio::SoundSource;
 use ggez::conf::{self};
-use ggez::event::{self, KeyCode, KeyMods};
+use ggez::event::{self, Axis, Button, GamepadId, KeyCode, KeyMods, MouseButton};
 use ggez::graphics::{self, Color};
 
 use ggez::nalgebra as na;
@@ -15,121 +15,375 @@ use std::env;
 use std::path;
 
 mod alien;
+mod answer_log;
 mod assets;
+mod audio;
 mod background;
+mod banner;
+mod choice;
+mod coop;
 mod crosshair;
+mod daily;
+mod dashboard;
+mod endless;
 mod explosion;
 mod ggez_utility;
+mod high_score;
+mod keypad;
+mod lan;
 mod level;
 mod mbtext;
 mod message;
+mod mode;
+mod personal_best;
+mod problem;
+mod profile;
+mod score;
+mod settings;
+mod sprint;
 mod turret;
+mod versus;
+mod voice;
+mod word_problem;
 
 use crate::alien::*;
+use crate::answer_log::*;
 use crate::assets::*;
+use crate::audio::*;
 use crate::background::*;
+use crate::banner::*;
+use crate::choice::*;
+use crate::coop::*;
 use crate::crosshair::*;
+use crate::daily::*;
+use crate::dashboard::*;
+use crate::endless::*;
 use crate::explosion::*;
 use crate::ggez_utility::*;
+use crate::high_score::*;
+use crate::keypad::*;
+use crate::lan::*;
 use crate::level::*;
 use crate::mbtext::*;
 use crate::message::*;
+use crate::mode::*;
+use crate::personal_best::*;
+use crate::problem::*;
+use crate::profile::*;
+use crate::score::*;
+use crate::settings::*;
+use crate::sprint::*;
 use crate::turret::*;
+use crate::versus::*;
+use crate::voice::*;
+use crate::word_problem::*;
 
+// the alien whose bottom edge is closest to getting through, going by where
+// they are now since patterns don't all fall at the same rate. exploding
+// aliens are only picked when there's nothing else left
 fn get_lowest_living_alien(aliens: &Vec<Alien>) -> Option<usize> {
     match aliens
         .iter()
         .enumerate()
         .filter(|(_, alien)| alien.state != AlienState::Dead)
-        .max_by_key(|(_, alien)| (alien.pos[1] * 1000.0) as i32)
-    {
+        .max_by_key(|(_, alien)| {
+            let bottom = alien.pos[1] + alien.pct_dimensions().1 / 2.0;
+            (alien.state == AlienState::Alive, (bottom * 1000.0) as i32)
+        }) {
         Some((index, _)) => Some(index),
         None => None,
     }
 }
 
-fn gen_aliens(wave: &Wave, assets: &Assets, difficulty: usize) -> Vec<Alien> {
+// the nearest alien on screen to the left (`direction` -1.0) or right (1.0)
+// of alien `from` as they are now, wrapping round to the far side when
+// there's none that way. aliens lined up at the same x, like a formation's
+// columns, are taken in index order so stepping still reaches every one
+fn next_alien_across(aliens: &[Alien], from: usize, direction: f32) -> Option<usize> {
+    let x = aliens[from].pos[0];
+    let across: Vec<(usize, f32, f32)> = aliens
+        .iter()
+        .enumerate()
+        .filter(|(i, alien)| *i != from && alien.state == AlienState::Alive && alien.pos[1] >= 0.0)
+        .map(|(i, alien)| {
+            let tie = (i as f32 - from as f32) * direction;
+            (i, (alien.pos[0] - x) * direction, tie)
+        })
+        .collect();
+    let closest = |a: &&(usize, f32, f32), b: &&(usize, f32, f32)| {
+        (a.1, a.2).partial_cmp(&(b.1, b.2)).unwrap()
+    };
+    across
+        .iter()
+        .filter(|(_, distance, tie)| *distance > 0.0 || (*distance == 0.0 && *tie > 0.0))
+        .min_by(closest)
+        .or_else(|| across.iter().min_by(closest))
+        .map(|(i, _, _)| *i)
+}
+
+const PLACEMENT_TRIES: usize = 20;
+// space between two aliens side by side, on top of their widths
+const ALIEN_GAP: f32 = 0.055;
+const FORMATION_COLUMNS: usize = 6;
+const FORMATION_SPACING: f32 = 0.1;
+
+// how much closer than it should be an alien at `x` would be to the last few
+// placed, 0.0 when it's clear. aliens moving the same way keep their distance,
+// but one that drifts sideways can run into one that moves differently
+fn crowding(aliens: &[Alien], x: f32, width: f32, pattern: Pattern) -> f32 {
+    aliens
+        .iter()
+        .rev()
+        .take(3)
+        .map(|alien| {
+            let mut gap = (alien.pct_dimensions().0 + width) / 2.0 + ALIEN_GAP;
+            if alien.pattern != pattern {
+                gap += alien.pattern.reach() + pattern.reach();
+            }
+            gap - (alien.home_x - x).abs()
+        })
+        .fold(0.0, f32::max)
+}
+
+// the first of a few random spots between `low` and `high` that isn't
+// crowded, or the least crowded when a wave is too packed for any to be clear
+fn place<R: Rng, F: Fn(f32) -> f32>(rng: &mut R, low: f32, high: f32, crowding: F) -> f32 {
+    let mut best = (f32::MAX, low);
+    for _ in 0..PLACEMENT_TRIES {
+        let x = rng.gen_range(low, high);
+        let crowded = crowding(x);
+        if crowded <= 0.0 {
+            return x;
+        }
+        if crowded < best.0 {
+            best = (crowded, x);
+        }
+    }
+    best.1
+}
+
+// red beams from both of the turret's barrels while it's firing at `alien`
+fn draw_lasers(turret: &Turret, alien: &Alien, ctx: &mut Context) {
+    if let TurretState::Resting = turret.state {
+        return;
+    }
+    let screen_size = graphics::size(ctx);
+    let turret_pos = turret.pixel_pos(screen_size);
+    //make the lasers come out of the actual gunscar
+    for dx in &[-0.01, 0.01] {
+        let barrel = na::Point2::new(
+            turret_pos[0] + dx * screen_size.0,
+            turret_pos[1] - 0.01 * screen_size.1,
+        );
+        if let Ok(laser) = graphics::Mesh::new_line(
+            ctx,
+            &[barrel, alien.pixel_pos(screen_size)],
+            4.0,
+            graphics::Color::from((255, 0, 0, 255)),
+        ) {
+            let _ = graphics::draw(ctx, &laser, graphics::DrawParam::default());
+        }
+    }
+}
+
+// top left corners of the items in a title screen menu, one per line
+// starting 40% of the way down the screen
+fn menu_positions(items: &[MBText], ctx: &mut Context) -> Vec<na::Point2<f32>> {
+    let window_dimension = graphics::size(ctx);
+    let mut y = 0.4 * window_dimension.1;
+    let mut positions = Vec::new();
+    for item in items {
+        let mut center = item.center(ctx);
+        center[1] = y;
+        positions.push(center);
+        y += item.dest_pixel_dimensions(window_dimension).1 * 1.075;
+    }
+    positions
+}
+
+fn menu_item_at(items: &[MBText], pos: na::Point2<f32>, ctx: &mut Context) -> Option<usize> {
+    let window_dimension = graphics::size(ctx);
+    menu_positions(items, ctx)
+        .iter()
+        .zip(items)
+        .position(|(corner, item)| {
+            let (w, h) = item.dest_pixel_dimensions(window_dimension);
+            graphics::Rect::new(corner[0], corner[1], w, h).contains(pos)
+        })
+}
+
+fn player_text(profiles: &Profiles, assets: &Assets, ctx: &mut Context) -> MBText {
+    let zen = if profiles.zen() { " (Zen)" } else { "" };
+    MBText::new_blink(
+        "Player: ".to_string() + profiles.current_name() + zen,
+        &assets.main_font,
+        WHITE,
+        GRAY,
+        48.0,
+        ctx,
+    )
+}
+
+fn settings_names(settings: &Settings, assets: &Assets, ctx: &mut Context) -> Vec<MBText> {
+    SETTINGS_ITEMS
+        .iter()
+        .map(|item| {
+            MBText::new_blink(
+                settings.item_label(*item),
+                &assets.main_font,
+                WHITE,
+                GRAY,
+                48.0,
+                ctx,
+            )
+        })
+        .collect()
+}
+
+// one line per action then a reset option, `rebinding` is the line
+// waiting for a new key
+fn binding_names(
+    settings: &Settings,
+    rebinding: Option<usize>,
+    assets: &Assets,
+    ctx: &mut Context,
+) -> Vec<MBText> {
+    let mut names: Vec<String> = ACTIONS
+        .iter()
+        .map(|action| settings.binding_label(*action))
+        .collect();
+    if let Some(i) = rebinding {
+        names[i] = ACTIONS[i].name().to_string() + ": Press A Key";
+    }
+    names.push("Reset To Defaults".to_string());
+    names
+        .into_iter()
+        .map(|name| MBText::new_blink(name, &assets.main_font, WHITE, GRAY, 48.0, ctx))
+        .collect()
+}
+
+fn gen_aliens<R: Rng>(wave: &Wave, assets: &Assets, difficulty: usize, rng: &mut R) -> Vec<Alien> {
     let mut aliens: Vec<Alien> = Vec::new();
-    let mut rng = rand::thread_rng();
     for group in &wave.groups {
-        let alien_img = match group.operation {
-            Operation::Add => &assets.add_ship,
-            Operation::Subtract => &assets.sub_ship,
-            Operation::Multiply => &assets.mul_ship,
-            Operation::Divide => &assets.div_ship,
+        let alien_img = match group.kind {
+            AlienKind::Normal => assets.ship(group.operation),
+            AlienKind::Boss { .. } => &assets.boss_ship,
         };
         let alien_img_width = alien_img.width() as f32;
         let alien_img_height = alien_img.height() as f32;
-        let num_ships = (group.num_ships as f32 * NUM_SHIPS_DIFFICULTY[difficulty]) as i32;
+        // however hard it's being played, a boss wave has just its bosses
+        let num_ships = match group.kind {
+            AlienKind::Normal => (group.num_ships as f32 * NUM_SHIPS_DIFFICULTY[difficulty]) as i32,
+            AlienKind::Boss { .. } => group.num_ships as i32,
+        };
+        let font_size = match group.kind {
+            AlienKind::Normal => 24.0,
+            AlienKind::Boss { .. } => 36.0,
+        };
+        // a boss always moves, even when its group doesn't say how
+        let pattern = match (group.kind, group.pattern) {
+            (AlienKind::Boss { .. }, Pattern::Straight) => Pattern::Sway,
+            (_, pattern) => pattern,
+        };
+        let width = alien_width(group.kind);
+        // room at the edges for the ship and however far it drifts
+        let margin = (width / 2.0).max(0.05) + pattern.reach();
+        // a formation is placed all at once, as rows of ships either side of
+        // the middle of the block
+        let columns = (num_ships.max(1) as usize).min(FORMATION_COLUMNS);
+        let offsets: Vec<f32> = (0..columns)
+            .map(|column| (column as f32 - (columns - 1) as f32 / 2.0) * FORMATION_SPACING)
+            .collect();
+        let formation = if pattern == Pattern::Formation {
+            let half = offsets[columns - 1];
+            let center = place(rng, margin + half, 1.0 - margin - half, |x| {
+                offsets
+                    .iter()
+                    .map(|offset| crowding(&aliens, x + offset, width, pattern))
+                    .fold(0.0, f32::max)
+            });
+            Some(center)
+        } else {
+            None
+        };
+        let word_problems = if group.operation == Operation::WordProblem {
+            Some(assets.word_problems.get(group.template_file.as_deref()))
+        } else {
+            None
+        };
         for i in 0..num_ships {
             let min_number = (group.min_number as f32 * MIN_NUMBER_DIFFICULTY[difficulty]) as i32;
             let max_number = (group.max_number as f32 * MAX_NUMBER_DIFFICULTY[difficulty]) as i32;
 
-            let (mut num1, mut num2) = if group.operation == Operation::Divide {
-                let mut a;
-                let mut b;
-                loop {
-                    a = rng.gen_range(min_number, max_number);
-                    b = if a == group.min_number {
-                        a
-                    } else {
-                        rng.gen_range(min_number, a)
+            // one problem per hit, a boss works through them in order
+            let mut problems: Vec<Pending> = (0..group.kind.hits())
+                .map(|_| {
+                    // word problems put the sentence in a banner and just a ? on the alien
+                    let (problem, text, banner) = match &word_problems {
+                        Some(word_problems) => {
+                            let problem = word_problems.gen_problem(difficulty, rng);
+                            let banner = Banner::new(problem.text.clone(), assets);
+                            (problem, group.operation.symbol().to_string(), Some(banner))
+                        }
+                        None => {
+                            let problem = gen_problem(
+                                group.operation,
+                                min_number,
+                                max_number,
+                                difficulty,
+                                rng,
+                            );
+                            let text = problem.text.clone();
+                            (problem, text, None)
+                        }
                     };
-                    if b == 0 {
-                        continue;
+                    let choices = gen_choices(&problem, assets, rng);
+                    Pending {
+                        problem,
+                        text: graphics::Text::new((text, assets.number_font, font_size)),
+                        choices,
+                        banner,
                     }
-                    if a % b == 0 {
-                        break;
-                    }
-                }
-                (a, b)
-            } else {
-                (
-                    rng.gen_range(min_number, max_number),
-                    rng.gen_range(min_number, max_number),
-                )
-            };
-
-            // make subtraction never negative until 3rd difficulty level
-            if difficulty < 2 && group.operation == Operation::Subtract {
-                let t = num2;
-                if num2 > num1 {
-                    num2 = num1;
-                    num1 = t;
-                }
-            }
-
-            let (answer, op) = match group.operation {
-                Operation::Add => (num1 + num2, "+"),
-                Operation::Subtract => (num1 - num2, "-"),
-                Operation::Multiply => (num1 * num2, "X"),
-                Operation::Divide => (num1 / num2, "/"),
-            };
-            let text = num1.to_string() + op + &num2.to_string();
+                })
+                .collect();
+            let first = problems.remove(0);
 
             // generate an x coordinate for aliens, make
             // sure it isn't too close to aliens at nearby
-            // y so they don't overlap
-            let mut x: f32 = rng.gen_range(0.05, 0.95);
-            while aliens
-                .iter()
-                .rev()
-                .take(3)
-                .any(|alien| (alien.pos[0] - x).abs() < 0.1)
-            {
-                x = rng.gen_range(0.05, 0.95);
-            }
+            // y so they don't overlap, wherever their patterns take them
+            let (x, y) = match formation {
+                Some(center) => {
+                    let row = i as usize / columns;
+                    let column = i as usize % columns;
+                    (center + offsets[column], -(row as f32) * FORMATION_SPACING)
+                }
+                None => {
+                    let x = place(rng, margin, 1.0 - margin, |x| {
+                        crowding(&aliens, x, width, pattern)
+                    });
+                    (x, -(i as i32) as f32 * 0.3)
+                }
+            };
 
             let alien = Alien {
                 operation: group.operation,
                 speed: group.speed as f32 * SPEED_DIFFICULTY[difficulty],
-                pos: na::Point2::new(x, -(i as i32) as f32 * 0.3),
-                text: graphics::Text::new((text, assets.number_font, 24.0)),
-                answer: answer,
+                pos: na::Point2::new(x, y),
+                text: first.text,
+                problem: first.problem,
+                choices: first.choices,
+                banner: first.banner,
                 explosion: Explosion::new(0.0, na::Point2::new(0.0, 0.0)),
                 state: AlienState::Alive,
                 src_pixel_width: alien_img_width,
                 src_pixel_height: alien_img_height,
+                kind: group.kind,
+                pattern,
+                pending: problems,
+                home_x: x,
+                age: 0.0,
+                spark: None,
+                debris: Vec::new(),
             };
             aliens.push(alien);
         }
@@ -138,18 +392,79 @@ fn gen_aliens(wave: &Wave, assets: &Assets, difficulty: usize) -> Vec<Alien> {
     aliens
 }
 
+const PAUSE_ITEMS: [&str; 4] = ["Resume", "Restart Wave", "Settings", "Quit To Menu"];
+const RESUME: usize = 0;
+const RESTART_WAVE: usize = 1;
+const SETTINGS: usize = 2;
+// zen practice shows the answer after this many misses on one alien
+const ZEN_ATTEMPTS: u32 = 3;
+// and aliens stop here, just above the turret
+const ZEN_FLOOR: f32 = 0.8;
+
+// room for a host name and port
+const MAX_ADDRESS_LENGTH: usize = 40;
+const LAN_ITEMS: [&str; 2] = ["Host A Match", "Join A Match"];
+const HOST: usize = 0;
+
+// what a typed name is for
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum NameFor {
+    Profile,
+    HighScore,
+    // the machine hosting a lan match
+    Address,
+}
+
+impl NameFor {
+    fn max_length(self) -> usize {
+        match self {
+            NameFor::Address => MAX_ADDRESS_LENGTH,
+            _ => MAX_NAME_LENGTH,
+        }
+    }
+
+    fn accepts(self, ch: char) -> bool {
+        match self {
+            NameFor::Address => ch.is_alphanumeric() || ch == '.' || ch == ':' || ch == '-',
+            _ => ch.is_alphanumeric() || ch == ' ',
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 enum GameState {
     DifficultySelect,
+    ModeSelect,
+    SprintSetup,
+    DailyCalendar,
+    CoOpSetup,
+    LanMenu,
+    LanLobby,
+    Settings,
+    KeyBindings,
+    ProfileSelect,
+    NameEntry(NameFor),
     LevelSelect,
+    HighScores,
+    Results,
     LevelComplete,
     LevelTransition(f32),
     Playing,
+    Sprint,
+    Versus,
+    Paused,
     Dying,
     Dead,
     Won,
 }
 
+impl GameState {
+    // aliens are falling and answers go to the turret
+    fn answering(&self) -> bool {
+        matches!(self, GameState::Playing | GameState::Sprint)
+    }
+}
+
 struct TextState {
     dead_text: MBText,
     won_text: MBText,
@@ -158,6 +473,27 @@ struct TextState {
     level_complete: MBText,
     level_names: Vec<MBText>,
     difficulty_names: Vec<MBText>,
+    mode_names: Vec<MBText>,
+    sprint_names: Vec<MBText>,
+    coop_names: Vec<MBText>,
+    lan_names: Vec<MBText>,
+    // why the last try at hosting or joining didn't work
+    lan_status: MBText,
+    input_mode_names: Vec<MBText>,
+    settings: MBText,
+    settings_names: Vec<MBText>,
+    binding_names: Vec<MBText>,
+    paused: MBText,
+    pause_names: Vec<MBText>,
+    results_title: MBText,
+    results: Vec<MBText>,
+    player: MBText,
+    profile_names: Vec<MBText>,
+    name_prompt: MBText,
+    name: MBText,
+    high_scores_hint: MBText,
+    high_scores_title: MBText,
+    high_scores: Vec<MBText>,
 }
 struct MainState {
     messages: VecDeque<Message>,
@@ -165,25 +501,86 @@ struct MainState {
     aliens: Vec<Alien>,
     assets: Assets,
     levels: Vec<Level>,
+    mode: Mode,
+    // endless mode's level, it gains a wave each time one is cleared
+    endless: Level,
+    personal_bests: PersonalBests,
+    sprint: Sprint,
+    daily: Level,
+    // the day being played, kept so a run past midnight stays on one challenge
+    daily_date: NaiveDate,
+    daily_history: DailyHistory,
+    // the first of the month shown on the calendar
+    calendar_month: NaiveDate,
+    versus: Level,
+    // both players' waves come from this, so they face the same problems
+    versus_seed: u64,
+    // player one on the left, player two on the right
+    sides: Vec<Side>,
+    coop: CoOp,
+    lan: Option<LanSession>,
+    lan_level: Level,
+    lan_seed: u64,
+    // the last host joined, so it's there to edit next time
+    lan_address: String,
+    // serving the teacher's dashboard, when started with --dashboard
+    dashboard: Option<Dashboard>,
+    // ms since the dashboard was last sent the session
+    dashboard_elapsed: f32,
+    // answers to each problem this session, for the dashboard
+    facts: FactTally,
     current_level: usize,
     current_wave: usize,
     turret: Turret,
     target: Option<usize>,
+    // the alien whose problem was last read aloud
+    spoken_target: Option<usize>,
     background: Background,
     state: GameState,
     text: TextState,
     lives: usize,
+    score: Score,
+    profiles: Profiles,
+    high_scores: HighScores,
+    profile_selection: usize,
+    // the profile whose table is shown on the high score screen
+    high_score_profile: usize,
+    name_entry: String,
+    // the alien being answered in zen practice and how many misses it's had
+    zen_misses: (Option<usize>, u32),
     crosshair: Crosshair,
     level_selection: usize,
     difficulty_selection: usize,
-    up_key: Option<KeyCode>,
+    mode_selection: usize,
+    sprint_selection: usize,
+    coop_selection: usize,
+    lan_selection: usize,
+    keypad: Keypad,
+    picker: Keypad,
+    // which way the left stick was last pushed, so holding it only moves once
+    stick: (i32, i32),
+    // what the last key or button released means, cleared after each update
+    action: Option<Action>,
+    settings: Settings,
+    settings_selection: usize,
+    binding_selection: usize,
+    pause_selection: usize,
+    // the settings screen was opened from the pause menu, so go back there
+    settings_from_pause: bool,
+    // waiting for the next key press to bind to the selected action
+    rebinding: bool,
 }
 
 impl MainState {
-    fn new(ctx: &mut Context) -> GameResult<MainState> {
+    fn new(ctx: &mut Context, settings: Settings) -> GameResult<MainState> {
         let levels = Level::load_from_file();
+        let profiles = Profiles::load_from_file();
         println!("levels count:{}",levels.len());
-        let assets = Assets::new(ctx, levels[0].background_file.clone());
+        let assets = Assets::new(
+            ctx,
+            levels[0].background_file.clone(),
+            TemplateCache::load(&levels),
+        );
         let messages = VecDeque::new();
         let aliens = Vec::new();
         let target = get_lowest_living_alien(&aliens);
@@ -234,6 +631,35 @@ impl MainState {
                         )
                     })
                     .collect(),
+                mode_names: MODES
+                    .iter()
+                    .map(|mode| {
+                        MBText::new_blink(
+                            mode.name().to_string(),
+                            &assets.main_font,
+                            WHITE,
+                            GRAY,
+                            64.0,
+                            ctx,
+                        )
+                    })
+                    .collect(),
+                sprint_names: Vec::new(),
+                coop_names: Vec::new(),
+                lan_names: LAN_ITEMS
+                    .iter()
+                    .map(|name| {
+                        MBText::new_blink(
+                            name.to_string(),
+                            &assets.main_font,
+                            WHITE,
+                            GRAY,
+                            64.0,
+                            ctx,
+                        )
+                    })
+                    .collect(),
+                lan_status: MBText::new(String::new(), &assets.main_font, GRAY, 40.0, ctx),
                 difficulty_names: DIFFICULTY_NAMES
                     .iter()
                     .map(|name| {
@@ -247,12 +673,86 @@ impl MainState {
                         )
                     })
                     .collect(),
+                input_mode_names: INPUT_MODES
+                    .iter()
+                    .map(|mode| {
+                        MBText::new(
+                            "< ".to_string() + mode.name() + " >",
+                            &assets.main_font,
+                            GRAY,
+                            48.0,
+                            ctx,
+                        )
+                    })
+                    .collect(),
+                settings: MBText::new_blink(
+                    "Settings".to_string(),
+                    &assets.main_font,
+                    WHITE,
+                    GRAY,
+                    48.0,
+                    ctx,
+                ),
+                settings_names: settings_names(&settings, &assets, ctx),
+                binding_names: binding_names(&settings, None, &assets, ctx),
+                paused: MBText::new("Paused".to_string(), &assets.title_font, BLUE, 128.0, ctx),
+                pause_names: PAUSE_ITEMS
+                    .iter()
+                    .map(|name| {
+                        MBText::new_blink(
+                            name.to_string(),
+                            &assets.main_font,
+                            WHITE,
+                            GRAY,
+                            64.0,
+                            ctx,
+                        )
+                    })
+                    .collect(),
+                results_title: MBText::new(String::new(), &assets.title_font, BLUE, 128.0, ctx),
+                results: Vec::new(),
+                player: player_text(&profiles, &assets, ctx),
+                profile_names: Vec::new(),
+                name_prompt: MBText::new(String::new(), &assets.main_font, WHITE, 64.0, ctx),
+                name: MBText::new(String::new(), &assets.main_font, BLUE, 64.0, ctx),
+                high_scores_hint: MBText::new(
+                    "High Scores >".to_string(),
+                    &assets.main_font,
+                    GRAY,
+                    48.0,
+                    ctx,
+                ),
+                high_scores_title: MBText::new(String::new(), &assets.title_font, BLUE, 128.0, ctx),
+                high_scores: Vec::new(),
             },
             turret: Turret::new(&assets, ctx),
+            keypad: Keypad::new("", &assets),
+            picker: Keypad::picker(number_keys(""), &assets),
+            stick: (0, 0),
             levels: levels,
+            mode: Mode::Campaign,
+            endless: endless_level(&mut rand::thread_rng()),
+            personal_bests: PersonalBests::load_from_file(),
+            sprint: Sprint::new(),
+            daily: daily_level(today()),
+            daily_date: today(),
+            daily_history: DailyHistory::load_from_file(),
+            calendar_month: add_months(today(), 0),
+            versus: versus_level(0),
+            versus_seed: 0,
+            sides: Vec::new(),
+            coop: CoOp::new(),
+            lan: None,
+            lan_level: lan_level(0),
+            lan_seed: 0,
+            lan_address: "127.0.0.1".to_string(),
+            dashboard: None,
+            dashboard_elapsed: 0.0,
+            facts: FactTally::new(),
             current_level: 0,
             current_wave: 0,
             target: target,
+            spoken_target: None,
             background: Background {
                 src_pixel_width: assets.background.width() as f32,
                 src_pixel_height: assets.background.height() as f32,
@@ -262,6 +762,13 @@ impl MainState {
             state: GameState::DifficultySelect,
             dt: std::time::Duration::new(0, 0),
             lives: 2,
+            score: Score::new(2),
+            profiles,
+            high_scores: HighScores::load_from_file(),
+            profile_selection: 0,
+            high_score_profile: 0,
+            name_entry: String::new(),
+            zen_misses: (None, 0),
             crosshair: Crosshair {
                 elapsed: 0,
                 src_pixel_width: assets.crosshair.width() as f32,
@@ -270,38 +777,130 @@ impl MainState {
             assets: assets,
             level_selection: 0,
             difficulty_selection: 0,
-            up_key: None,
+            mode_selection: 0,
+            sprint_selection: 0,
+            coop_selection: 0,
+            lan_selection: 0,
+            action: None,
+            settings,
+            settings_selection: 0,
+            binding_selection: 0,
+            pause_selection: 0,
+            settings_from_pause: false,
+            rebinding: false,
         })
     }
 
+    // the level being played
+    fn level(&self) -> &Level {
+        match self.mode {
+            Mode::Campaign | Mode::CoOp => &self.levels[self.current_level],
+            Mode::Endless => &self.endless,
+            Mode::MadMinute => &self.sprint.level,
+            Mode::Daily => &self.daily,
+            Mode::Versus => &self.versus,
+            Mode::Lan => &self.lan_level,
+        }
+    }
+
+    // the daily challenge and lan matches are the same for everyone, whatever
+    // they picked
+    fn difficulty(&self) -> usize {
+        match (self.mode, &self.lan) {
+            (Mode::Daily, _) => DAILY_DIFFICULTY,
+            (Mode::Lan, Some(lan)) => lan.difficulty,
+            _ => self.difficulty_selection,
+        }
+    }
+
     fn load_level_wave(&mut self, level: usize, wave: usize) {
         self.current_level = level;
         self.current_wave = wave;
         self.target = None;
-        let wave = &self.levels[self.current_level].waves[self.current_wave];
-        self.aliens = gen_aliens(wave, &self.assets, self.difficulty_selection);
+        let wave = &self.level().waves[self.current_wave];
+        let aliens = match self.mode {
+            Mode::Daily => {
+                let mut rng = wave_rng(self.daily_date, self.current_wave);
+                gen_aliens(wave, &self.assets, self.difficulty(), &mut rng)
+            }
+            Mode::Lan => {
+                let mut rng = seeded_rng(self.lan_seed, self.current_wave);
+                gen_aliens(wave, &self.assets, self.difficulty(), &mut rng)
+            }
+            Mode::MadMinute => self.sprint_batch(),
+            _ => gen_aliens(
+                wave,
+                &self.assets,
+                self.difficulty(),
+                &mut rand::thread_rng(),
+            ),
+        };
+        let extra_input = wave.extra_input();
+        self.aliens = aliens;
         self.target = get_lowest_living_alien(&self.aliens);
+        self.spoken_target = None;
+        self.score.reset_timer();
+        // the keypad grows keys for symbols this wave's answers need
+        let visible = self.keypad.visible;
+        self.keypad = Keypad::new(&extra_input, &self.assets);
+        self.keypad.visible = visible;
+        self.rebuild_picker();
+    }
+
+    // gamepads pick digits in typed mode and choice numbers otherwise
+    fn rebuild_picker(&mut self) {
+        let wave = &self.level().waves[self.current_wave];
+        let picker_keys = match self.settings.input_mode {
+            InputMode::Typed => number_keys(&wave.extra_input()),
+            InputMode::MultipleChoice => (1..=MAX_CHOICES as u32)
+                .filter_map(|n| std::char::from_digit(n, 10))
+                .map(Key::Char)
+                .collect(),
+        };
+        let visible = self.picker.visible;
+        self.picker = Keypad::picker(picker_keys, &self.assets);
+        self.picker.visible = visible;
     }
 
     fn set_level_wave(&mut self, level: usize, wave: usize) {
         if level > self.current_level {
             self.state = GameState::LevelComplete;
-            let _ = self.assets.clap_sound.play_detached();
         }
         self.load_level_wave(level, wave);
     }
     fn increment_level_wave(&mut self, ctx: &mut Context) {
-        //if we were at the last wave already then go to next level
-        if self.current_wave + 1 >= self.levels[self.current_level].waves.len() {            
-            if self.current_level + 1 >= self.levels.len() {
-                self.state = GameState::Won;
-                let _ = self.assets.clap_sound.play_detached();
-            } else {
-                //unlock the next level and save the json
-                self.levels[self.current_level + 1].unlocked[self.difficulty_selection] = true;
+        // endless never runs out of waves
+        if self.mode == Mode::Endless {
+            let wave = endless_wave(self.current_wave + 1, &mut rand::thread_rng());
+            self.endless.waves.push(wave);
+        }
+        if self.mode == Mode::Lan {
+            self.report_lan_progress(self.current_wave + 1);
+        }
+        //if we were at the last wave already then show the results, then go to the next level
+        if self.current_wave + 1 >= self.level().waves.len() && self.mode == Mode::Lan {
+            self.assets.audio.play(Sound::Clap);
+            self.finish_lan_run(DAILY_WAVES, ctx);
+        } else if self.current_wave + 1 >= self.level().waves.len() && self.mode == Mode::Daily {
+            self.assets.audio.play(Sound::Clap);
+            self.show_daily_results(DAILY_WAVES, ctx);
+        } else if self.current_wave + 1 >= self.level().waves.len() && self.mode == Mode::CoOp {
+            self.assets.audio.play(Sound::Clap);
+            self.show_coop_results(ctx);
+        } else if self.current_wave + 1 >= self.level().waves.len() {
+            //keep the best stars, unlock whatever that opens up and save the json
+            if !self.zen() {
+                let stars = self.score.stars(self.lives);
+                Level::complete(
+                    &mut self.levels,
+                    self.current_level,
+                    self.difficulty_selection,
+                    stars,
+                );
                 Level::save_levels(&self.levels);
-                self.set_level_wave(self.current_level + 1, 0)
             }
+            self.assets.audio.play(Sound::Clap);
+            self.show_results(ctx);
         } else {
             self.set_level_wave(self.current_level, self.current_wave + 1);
             self.messages.push_back(Message::new(
@@ -316,141 +915,1309 @@ impl MainState {
                 &self.assets,
                 ctx,
             ));
+            // each wave goes to the next player in the hot seat
+            if self.mode == Mode::CoOp {
+                self.coop.next_turn(&mut self.score);
+                self.push_turn_message(ctx);
+            }
         }
     }
-    fn update_difficulty_select(&mut self, _ctx: &mut Context) {
+    fn update_difficulty_select(&mut self, ctx: &mut Context) {
         for difficulty in &mut self.text.difficulty_names {
             difficulty.update(self.dt);
         }
-        if let Some(keycode) = self.up_key {
-            if keycode == KeyCode::Return {
-                self.state = GameState::LevelSelect;
-            } else if keycode == KeyCode::Down {
-                self.difficulty_selection =
-                    (self.difficulty_selection + 1) % DIFFICULTY_NAMES.len();
-            } else if keycode == KeyCode::Up {
+        self.text.settings.update(self.dt);
+        self.text.player.update(self.dt);
+        // the settings and player entries sit after the difficulties, so selecting
+        // them never reaches level select with a difficulty out of range
+        let item_count = DIFFICULTY_NAMES.len() + 2;
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                if self.difficulty_selection == DIFFICULTY_NAMES.len() {
+                    self.settings_from_pause = false;
+                    self.settings_selection = 0;
+                    self.state = GameState::Settings;
+                } else if self.difficulty_selection == DIFFICULTY_NAMES.len() + 1 {
+                    self.profile_selection = self.profiles.current;
+                    self.refresh_profile_names(ctx);
+                    self.state = GameState::ProfileSelect;
+                } else {
+                    self.state = GameState::ModeSelect;
+                }
+            } else if action == Action::MenuDown {
+                self.difficulty_selection = (self.difficulty_selection + 1) % item_count;
+            } else if action == Action::MenuUp {
                 self.difficulty_selection = if self.difficulty_selection == 0 {
-                    DIFFICULTY_NAMES.len() - 1
+                    item_count - 1
                 } else {
                     self.difficulty_selection - 1
                 };
+            } else if action == Action::TargetPrev {
+                self.adjust_setting(ctx, SettingsItem::InputMode, -1);
+            } else if action == Action::TargetNext {
+                self.adjust_setting(ctx, SettingsItem::InputMode, 1);
             }
         }
     }
 
-    fn update_level_select(&mut self, ctx: &mut Context) {
-        let unlocked_count = self
-            .levels
-            .iter()
-            .filter(|level| level.unlocked[self.difficulty_selection])
-            .count();
-        if let Some(keycode) = self.up_key {
-            if keycode == KeyCode::Return {
-                self.load_level_wave(self.level_selection, 0);
-                self.assets.background = graphics::Image::new(
-                    ctx,
-                    self.levels[self.current_level].background_file.clone(),
-                )
-                .unwrap();
-                self.messages.push_back(Message::new(
-                    self.levels[self.level_selection].title.clone(),
-                    2000.0,
-                    &self.assets,
-                    ctx,
-                ));
-                self.messages.push_back(Message::new(
-                    "Wave 1".to_string(),
-                    2000.0,
-                    &self.assets,
-                    ctx,
-                ));
-                self.lives = 2;
-                self.turret = Turret::new(&self.assets, ctx);
-                self.state = GameState::Playing;
-            } else if keycode == KeyCode::Down {
-                self.level_selection = (self.level_selection + 1) % unlocked_count;
-            } else if keycode == KeyCode::Up {
-                self.level_selection = if self.level_selection == 0 {
-                    unlocked_count - 1
+    // every profile, then lines for adding a new one and switching zen
+    // practice on or off for the current one
+    fn refresh_profile_names(&mut self, ctx: &mut Context) {
+        let mut names = self.profiles.names.clone();
+        names.push("New Player".to_string());
+        let zen = if self.profiles.zen() { "On" } else { "Off" };
+        names.push("Zen Practice: ".to_string() + zen);
+        self.text.profile_names = names
+            .into_iter()
+            .map(|name| MBText::new_blink(name, &self.assets.main_font, WHITE, GRAY, 48.0, ctx))
+            .collect();
+    }
+
+    fn update_profile_select(&mut self, ctx: &mut Context) {
+        for profile_name in &mut self.text.profile_names {
+            profile_name.update(self.dt);
+        }
+        let item_count = self.text.profile_names.len();
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                if self.profile_selection < self.profiles.names.len() {
+                    if self.profiles.current != self.profile_selection {
+                        self.facts = FactTally::new();
+                    }
+                    self.profiles.current = self.profile_selection;
+                    self.profiles.save();
+                    self.text.player = player_text(&self.profiles, &self.assets, ctx);
+                    self.state = GameState::DifficultySelect;
+                } else if self.profile_selection == self.profiles.names.len() {
+                    self.start_name_entry(ctx, NameFor::Profile, String::new());
+                } else {
+                    self.profiles.toggle_zen();
+                    self.profiles.save();
+                    self.refresh_profile_names(ctx);
+                    self.text.player = player_text(&self.profiles, &self.assets, ctx);
+                }
+            } else if action == Action::MenuDown {
+                self.profile_selection = (self.profile_selection + 1) % item_count;
+            } else if action == Action::MenuUp {
+                self.profile_selection = if self.profile_selection == 0 {
+                    item_count - 1
                 } else {
-                    self.level_selection - 1
+                    self.profile_selection - 1
                 };
             }
         }
-        for level_name in &mut self.text.level_names {
-            level_name.update(self.dt)
-        }
     }
-    fn update_won(&mut self, _ctx: &mut Context) {
-        if let Some(keycode) = self.up_key {
-            if keycode == KeyCode::Return {
-                self.state = GameState::DifficultySelect;
+
+    fn start_name_entry(&mut self, ctx: &mut Context, name_for: NameFor, name: String) {
+        let prompt = match name_for {
+            NameFor::Profile => "Type Your Name",
+            NameFor::HighScore => "New High Score! Type Your Name",
+            NameFor::Address => "Type The Host's Address",
+        };
+        self.text.name_prompt =
+            MBText::new(prompt.to_string(), &self.assets.main_font, WHITE, 64.0, ctx);
+        self.name_entry = name;
+        self.refresh_name(ctx);
+        self.state = GameState::NameEntry(name_for);
+    }
+
+    fn refresh_name(&mut self, ctx: &mut Context) {
+        self.text.name = MBText::new(
+            self.name_entry.clone() + "_",
+            &self.assets.main_font,
+            BLUE,
+            64.0,
+            ctx,
+        );
+    }
+
+    fn update_name_entry(&mut self, ctx: &mut Context, name_for: NameFor) {
+        if let Some(action) = self.action {
+            if action == Action::Back {
+                let _ = self.name_entry.pop();
+                self.refresh_name(ctx);
+            } else if action == Action::Confirm && !self.name_entry.trim().is_empty() {
+                let name = self.name_entry.trim().to_string();
+                match name_for {
+                    NameFor::Profile => {
+                        self.profiles.add(name);
+                        self.profiles.save();
+                        self.text.player = player_text(&self.profiles, &self.assets, ctx);
+                        self.state = GameState::DifficultySelect;
+                    }
+                    NameFor::HighScore => {
+                        let score = HighScore {
+                            name,
+                            score: self.score.points,
+                        };
+                        self.high_scores.insert(
+                            &self.levels[self.current_level].title,
+                            self.difficulty_selection,
+                            self.profiles.current_name(),
+                            score,
+                        );
+                        self.high_scores.save();
+                        self.finish_level();
+                    }
+                    NameFor::Address => {
+                        self.lan_address = name;
+                        match LanSession::join(&self.lan_address, self.profiles.current_name()) {
+                            Ok(lan) => {
+                                self.lan = Some(lan);
+                                let waiting = "Waiting For The Host To Start".to_string();
+                                self.set_lan_status(ctx, waiting);
+                                self.state = GameState::LanLobby;
+                            }
+                            Err(e) => {
+                                self.set_lan_status(ctx, format!("Couldn't Join: {}", e));
+                                self.state = GameState::LanMenu;
+                            }
+                        }
+                    }
+                }
             }
         }
     }
-    fn update_level_complete(&mut self, ctx: &mut Context) {
-        self.turret.rotation = 0.0;
-        self.background.update(self.dt, 1.0);
-        if let Some(keycode) = self.up_key {
-            if keycode == KeyCode::Return {
-                let _ = self.assets.launch_sound.play_detached();
-                self.state = GameState::LevelTransition(0.0);
-                self.levels[self.current_level].push_title(&mut self.messages, &self.assets, ctx);
-                self.messages.push_back(Message::new(
-                    "WARP SPEED".to_string(),
-                    2000.0,
-                    &self.assets,
-                    ctx,
-                ));
+
+    fn show_high_scores(&mut self, ctx: &mut Context) {
+        let level = &self.levels[self.level_selection].title;
+        let profile = &self.profiles.names[self.high_score_profile];
+        self.text.high_scores_title =
+            MBText::new(level.clone(), &self.assets.title_font, BLUE, 128.0, ctx);
+        let mut lines = vec![format!(
+            "< {} - {} >",
+            profile, DIFFICULTY_NAMES[self.difficulty_selection]
+        )];
+        let scores = self
+            .high_scores
+            .scores(level, self.difficulty_selection, profile);
+        if scores.is_empty() {
+            lines.push("No Scores Yet".to_string());
+        }
+        for (i, entry) in scores.iter().enumerate() {
+            lines.push(format!("{}. {}  {}", i + 1, entry.name, entry.score));
+        }
+        self.text.high_scores = lines
+            .into_iter()
+            .map(|line| MBText::new(line, &self.assets.main_font, WHITE, 48.0, ctx))
+            .collect();
+        self.state = GameState::HighScores;
+    }
+
+    // left and right flip through the other players' tables
+    fn update_high_scores(&mut self, ctx: &mut Context) {
+        let count = self.profiles.names.len();
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                self.state = GameState::LevelSelect;
+            } else if action == Action::TargetNext {
+                self.high_score_profile = (self.high_score_profile + 1) % count;
+                self.show_high_scores(ctx);
+            } else if action == Action::TargetPrev {
+                self.high_score_profile = (self.high_score_profile + count - 1) % count;
+                self.show_high_scores(ctx);
             }
         }
     }
-    fn update_level_transition(&mut self, ctx: &mut Context, elapsed: f32) {
-        self.state = GameState::LevelTransition(elapsed + self.dt.as_millis() as f32);
-        let pct = elapsed / 3000.0;
-        self.background.update(self.dt, 1.0 + pct * 1.0);
-        self.turret.pos[1] -= 0.015 * pct;
 
-        if elapsed >= 3000.0 {
-            self.state = GameState::Playing;
-            self.levels[self.current_level].push_title(&mut self.messages, &self.assets, ctx);
-            self.messages.push_back(Message::new(
-                "Wave 1".to_string(),
-                2000.0,
-                &self.assets,
-                ctx,
-            ));
-            self.assets.background =
-                graphics::Image::new(ctx, self.levels[self.current_level].background_file.clone())
-                    .unwrap();
-            self.turret.pos = na::Point2::new(0.5, 0.9); //put turret back at the bottom
+    fn update_settings(&mut self, ctx: &mut Context) {
+        for settings_name in &mut self.text.settings_names {
+            settings_name.update(self.dt);
+        }
+        let item = SETTINGS_ITEMS[self.settings_selection];
+        if let Some(action) = self.action {
+            if action == Action::Confirm && item == SettingsItem::Controls {
+                self.binding_selection = 0;
+                self.state = GameState::KeyBindings;
+            } else if action == Action::Confirm {
+                self.settings.advance(item);
+                self.setting_changed(ctx, item);
+            } else if action == Action::TargetNext {
+                self.adjust_setting(ctx, item, 1);
+            } else if action == Action::TargetPrev {
+                self.adjust_setting(ctx, item, -1);
+            } else if action == Action::MenuDown {
+                self.settings_selection = (self.settings_selection + 1) % SETTINGS_ITEMS.len();
+            } else if action == Action::MenuUp {
+                self.settings_selection = if self.settings_selection == 0 {
+                    SETTINGS_ITEMS.len() - 1
+                } else {
+                    self.settings_selection - 1
+                };
+            }
         }
     }
 
-    fn update_dead(&mut self, _ctx: &mut Context) {
-        if let Some(keycode) = self.up_key {
-            if keycode == KeyCode::Return {
-                self.state = GameState::LevelSelect;
+    // changes take effect right away and are saved as they're made
+    fn adjust_setting(&mut self, ctx: &mut Context, item: SettingsItem, direction: i32) {
+        self.settings.adjust(item, direction);
+        self.setting_changed(ctx, item);
+    }
+
+    fn setting_changed(&mut self, ctx: &mut Context, item: SettingsItem) {
+        self.settings.save();
+        self.text.settings_names = settings_names(&self.settings, &self.assets, ctx);
+        match item {
+            SettingsItem::Fullscreen | SettingsItem::Resolution => {
+                if let Err(e) = graphics::set_mode(ctx, self.settings.window_mode()) {
+                    println!("couldn't change the window mode\n{}", e);
+                }
             }
+            SettingsItem::InputMode => self.rebuild_picker(),
+            SettingsItem::Voice => {
+                self.assets.audio.interrupt();
+                self.spoken_target = None;
+            }
+            _ => self.apply_volume(),
         }
     }
 
-    fn update_dying(&mut self, ctx: &mut Context) {
-        for alien in &mut self.aliens {
-            alien.update(&mut self.turret, ctx, self.dt);
+    fn apply_volume(&mut self) {
+        let audio = &mut self.assets.audio;
+        audio.set_volume(Bus::Music, self.settings.music_level());
+        audio.set_volume(Bus::Sfx, self.settings.sfx_level());
+        audio.set_volume(Bus::Voice, self.settings.voice_level());
+    }
+
+    fn update_key_bindings(&mut self, ctx: &mut Context) {
+        for binding_name in &mut self.text.binding_names {
+            binding_name.update(self.dt);
         }
-        self.turret.update(ctx, self.dt);
-        for splosion in &mut self.turret.explosions {
-            splosion.update(ctx, self.dt);
+        let item_count = self.text.binding_names.len();
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                if self.binding_selection < ACTIONS.len() {
+                    self.rebinding = true;
+                } else {
+                    self.settings.bindings = Settings::new().bindings;
+                    self.settings.save();
+                }
+                self.refresh_binding_names(ctx);
+            } else if action == Action::MenuDown {
+                self.binding_selection = (self.binding_selection + 1) % item_count;
+            } else if action == Action::MenuUp {
+                self.binding_selection = if self.binding_selection == 0 {
+                    item_count - 1
+                } else {
+                    self.binding_selection - 1
+                };
+            }
         }
-        if self
-            .turret
+    }
+
+    fn refresh_binding_names(&mut self, ctx: &mut Context) {
+        let rebinding = if self.rebinding {
+            Some(self.binding_selection)
+        } else {
+            None
+        };
+        self.text.binding_names = binding_names(&self.settings, rebinding, &self.assets, ctx);
+    }
+
+    fn update_mode_select(&mut self, ctx: &mut Context) {
+        for mode_name in &mut self.text.mode_names {
+            mode_name.update(self.dt);
+        }
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                self.mode = MODES[self.mode_selection];
+                match self.mode {
+                    Mode::Campaign => {
+                        if !self.level_open(self.level_selection) {
+                            self.level_selection = (0..self.levels.len())
+                                .find(|i| self.level_open(*i))
+                                .unwrap_or(0);
+                        }
+                        self.state = GameState::LevelSelect;
+                    }
+                    Mode::Endless => {
+                        self.endless = endless_level(&mut rand::thread_rng());
+                        self.load_level_wave(0, 0);
+                        self.start_playing(ctx);
+                    }
+                    Mode::MadMinute => {
+                        self.refresh_sprint_names(ctx);
+                        self.state = GameState::SprintSetup;
+                    }
+                    Mode::Daily => {
+                        self.calendar_month = add_months(today(), 0);
+                        self.state = GameState::DailyCalendar;
+                    }
+                    Mode::Versus => self.start_versus(ctx),
+                    Mode::Lan => {
+                        self.lan_selection = 0;
+                        self.set_lan_status(ctx, String::new());
+                        self.state = GameState::LanMenu;
+                    }
+                    Mode::CoOp => {
+                        self.coop_selection = 0;
+                        if !self.levels[self.coop.level].unlocked[self.difficulty_selection] {
+                            self.coop.level = 0;
+                        }
+                        self.refresh_coop_names(ctx);
+                        self.state = GameState::CoOpSetup;
+                    }
+                }
+            } else if action == Action::MenuDown {
+                self.mode_selection = (self.mode_selection + 1) % MODES.len();
+            } else if action == Action::MenuUp {
+                self.mode_selection = if self.mode_selection == 0 {
+                    MODES.len() - 1
+                } else {
+                    self.mode_selection - 1
+                };
+            }
+        }
+    }
+
+    // starts the first wave of the loaded level with fresh lives and score
+    fn start_playing(&mut self, ctx: &mut Context) {
+        let music = self.level().music().to_string();
+        self.assets.audio.play_music(ctx, &music, 1000.0);
+        self.assets.background =
+            graphics::Image::new(ctx, self.level().background_file.clone()).unwrap();
+        self.messages.push_back(Message::new(
+            self.level().title.clone(),
+            2000.0,
+            &self.assets,
+            ctx,
+        ));
+        self.messages.push_back(Message::new(
+            "Wave 1".to_string(),
+            2000.0,
+            &self.assets,
+            ctx,
+        ));
+        self.lives = 2;
+        self.score = Score::new(self.lives);
+        self.turret = Turret::new(&self.assets, ctx);
+        self.state = GameState::Playing;
+    }
+
+    // endless is scored by waves cleared, the one that ended the run doesn't count
+    fn show_endless_results(&mut self, ctx: &mut Context) {
+        let waves = self.current_wave as u32;
+        let profile = self.profiles.current_name().to_string();
+        let old_best = self
+            .personal_bests
+            .best(Mode::Endless, &profile, self.difficulty_selection);
+        let mut lines = vec![format!("Waves Survived: {}", waves)];
+        if self
+            .personal_bests
+            .record(Mode::Endless, &profile, self.difficulty_selection, waves)
+        {
+            self.personal_bests.save();
+            if old_best.is_some() {
+                lines.push("New Personal Best!".to_string());
+            }
+        } else if let Some(best) = old_best {
+            lines.push(format!("Personal Best: {}", best));
+        }
+        lines.extend(self.score.summary());
+        self.text.results_title = MBText::new(
+            self.endless.title.clone(),
+            &self.assets.title_font,
+            BLUE,
+            128.0,
+            ctx,
+        );
+        self.text.results = lines
+            .into_iter()
+            .map(|line| MBText::new(line, &self.assets.main_font, WHITE, 48.0, ctx))
+            .collect();
+        self.state = GameState::Results;
+    }
+
+    // back to where this run was picked
+    fn quit_to_menu(&mut self) {
+        self.state = match self.mode {
+            Mode::Campaign => GameState::LevelSelect,
+            Mode::Endless | Mode::Versus => GameState::ModeSelect,
+            Mode::MadMinute => GameState::SprintSetup,
+            Mode::Daily => GameState::DailyCalendar,
+            Mode::CoOp => GameState::CoOpSetup,
+            Mode::Lan if self.lan.is_some() => GameState::LanLobby,
+            Mode::Lan => GameState::LanMenu,
+        };
+    }
+
+    // left and right page through the months, enter plays today's challenge
+    fn update_daily_calendar(&mut self, ctx: &mut Context) {
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                self.daily_date = today();
+                self.daily = daily_level(self.daily_date);
+                self.load_level_wave(0, 0);
+                self.start_playing(ctx);
+            } else if action == Action::TargetPrev {
+                self.calendar_month = add_months(self.calendar_month, -1);
+            } else if action == Action::TargetNext {
+                self.calendar_month = add_months(self.calendar_month, 1);
+            }
+        }
+    }
+
+    fn show_daily_results(&mut self, waves: usize, ctx: &mut Context) {
+        let profile = self.profiles.current_name().to_string();
+        let old_score = self
+            .daily_history
+            .result(self.daily_date, &profile)
+            .map(|result| result.score);
+        let mut lines = vec![format!("Waves Cleared: {} of {}", waves, DAILY_WAVES)];
+        if self
+            .daily_history
+            .record(self.daily_date, &profile, self.score.points, waves)
+        {
+            self.daily_history.save();
+            if old_score.is_some() {
+                lines.push("New Best Today!".to_string());
+            }
+        } else if let Some(score) = old_score {
+            lines.push(format!("Best Today: {}", score));
+        }
+        lines.extend(self.score.summary());
+        self.text.results_title = MBText::new(
+            self.daily.title.clone(),
+            &self.assets.title_font,
+            BLUE,
+            128.0,
+            ctx,
+        );
+        self.text.results = lines
+            .into_iter()
+            .map(|line| MBText::new(line, &self.assets.main_font, WHITE, 48.0, ctx))
+            .collect();
+        self.state = GameState::Results;
+    }
+
+    fn refresh_sprint_names(&mut self, ctx: &mut Context) {
+        self.text.sprint_names = SPRINT_ITEMS
+            .iter()
+            .map(|item| {
+                MBText::new_blink(
+                    self.sprint.item_label(*item),
+                    &self.assets.main_font,
+                    WHITE,
+                    GRAY,
+                    48.0,
+                    ctx,
+                )
+            })
+            .collect();
+    }
+
+    fn update_sprint_setup(&mut self, ctx: &mut Context) {
+        for sprint_name in &mut self.text.sprint_names {
+            sprint_name.update(self.dt);
+        }
+        let item = SPRINT_ITEMS[self.sprint_selection];
+        if let Some(action) = self.action {
+            if action == Action::Confirm && item == SprintItem::Start {
+                self.start_sprint(ctx);
+            } else if action == Action::Confirm || action == Action::TargetNext {
+                self.sprint.adjust(item, 1);
+                self.refresh_sprint_names(ctx);
+            } else if action == Action::TargetPrev {
+                self.sprint.adjust(item, -1);
+                self.refresh_sprint_names(ctx);
+            } else if action == Action::MenuDown {
+                self.sprint_selection = (self.sprint_selection + 1) % SPRINT_ITEMS.len();
+            } else if action == Action::MenuUp {
+                self.sprint_selection = if self.sprint_selection == 0 {
+                    SPRINT_ITEMS.len() - 1
+                } else {
+                    self.sprint_selection - 1
+                };
+            }
+        }
+    }
+
+    fn start_sprint(&mut self, ctx: &mut Context) {
+        self.sprint.start(self.difficulty_selection);
+        self.load_level_wave(0, 0);
+        let music = self.level().music().to_string();
+        self.assets.audio.play_music(ctx, &music, 1000.0);
+        self.assets.background =
+            graphics::Image::new(ctx, self.level().background_file.clone()).unwrap();
+        self.messages.clear();
+        self.messages.push_back(Message::new(
+            self.level().title.clone(),
+            2000.0,
+            &self.assets,
+            ctx,
+        ));
+        self.score = Score::new(self.lives);
+        self.turret = Turret::new(&self.assets, ctx);
+        self.state = GameState::Sprint;
+    }
+
+    // the sprint's wave already has the difficulty's speed in it and its
+    // numbers are the ones picked, so every batch is built unscaled
+    fn sprint_batch(&self) -> Vec<Alien> {
+        gen_aliens(
+            &self.sprint.level.waves[0],
+            &self.assets,
+            0,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    // no lives to lose, aliens that get through just count as missed and
+    // more keep coming until the clock runs out
+    fn update_sprint(&mut self, ctx: &mut Context) {
+        self.update_field(ctx);
+        for alien in &mut self.aliens {
+            if alien.state == AlienState::Alive && alien.pos[1] > 0.9 {
+                alien.state = AlienState::Dead;
+                self.sprint.missed += 1;
+            }
+        }
+        if self
+            .aliens
+            .iter()
+            .all(|alien| alien.state != AlienState::Alive || alien.pos[1] >= 0.0)
+        {
+            let batch = self.sprint_batch();
+            self.aliens.extend(batch);
+            if self.target.is_none() {
+                self.target = get_lowest_living_alien(&self.aliens);
+            }
+        }
+        self.sprint.remaining -= self.dt.as_millis() as f32;
+        if self.sprint.remaining <= 0.0 {
+            self.show_sprint_results(ctx);
+        }
+    }
+
+    fn show_sprint_results(&mut self, ctx: &mut Context) {
+        self.assets.audio.play(Sound::Clap);
+        self.messages.clear();
+        self.text.results_title = MBText::new(
+            self.sprint.level.title.clone(),
+            &self.assets.title_font,
+            BLUE,
+            128.0,
+            ctx,
+        );
+        self.text.results = self
+            .sprint
+            .summary(&self.score)
+            .into_iter()
+            .map(|line| MBText::new(line, &self.assets.main_font, WHITE, 64.0, ctx))
+            .collect();
+        self.state = GameState::Results;
+    }
+
+    // every profile that can join, then the level and a start line
+    fn refresh_coop_names(&mut self, ctx: &mut Context) {
+        let mut names: Vec<String> = self
+            .profiles
+            .names
+            .iter()
+            .map(|name| {
+                let playing = if self.coop.playing(name) { "In" } else { "Out" };
+                format!("{}: {}", name, playing)
+            })
+            .collect();
+        names.push(format!("< {} >", self.levels[self.coop.level].title));
+        names.push(if self.coop.ready() {
+            "Start".to_string()
+        } else {
+            format!("Pick {} Or More Players", MIN_PLAYERS)
+        });
+        self.text.coop_names = names
+            .into_iter()
+            .map(|name| MBText::new_blink(name, &self.assets.main_font, WHITE, GRAY, 48.0, ctx))
+            .collect();
+    }
+
+    // only levels unlocked at this difficulty can be picked
+    fn move_coop_level(&mut self, forward: bool) {
+        let count = self.levels.len();
+        for _ in 0..count {
+            self.coop.level = if forward {
+                (self.coop.level + 1) % count
+            } else {
+                (self.coop.level + count - 1) % count
+            };
+            if self.levels[self.coop.level].unlocked[self.difficulty_selection] {
+                break;
+            }
+        }
+    }
+
+    fn update_coop_setup(&mut self, ctx: &mut Context) {
+        for coop_name in &mut self.text.coop_names {
+            coop_name.update(self.dt);
+        }
+        let item_count = self.text.coop_names.len();
+        let level_item = self.profiles.names.len();
+        if let Some(action) = self.action {
+            if action == Action::Confirm && self.coop_selection < level_item {
+                let name = self.profiles.names[self.coop_selection].clone();
+                self.coop.toggle(&name);
+                self.refresh_coop_names(ctx);
+            } else if action == Action::Confirm && self.coop_selection > level_item {
+                if self.coop.ready() {
+                    self.start_coop(ctx);
+                }
+            } else if self.coop_selection == level_item
+                && (action == Action::Confirm || action == Action::TargetNext)
+            {
+                self.move_coop_level(true);
+                self.refresh_coop_names(ctx);
+            } else if self.coop_selection == level_item && action == Action::TargetPrev {
+                self.move_coop_level(false);
+                self.refresh_coop_names(ctx);
+            } else if action == Action::MenuDown {
+                self.coop_selection = (self.coop_selection + 1) % item_count;
+            } else if action == Action::MenuUp {
+                self.coop_selection = if self.coop_selection == 0 {
+                    item_count - 1
+                } else {
+                    self.coop_selection - 1
+                };
+            }
+        }
+    }
+
+    fn start_coop(&mut self, ctx: &mut Context) {
+        self.load_level_wave(self.coop.level, 0);
+        self.start_playing(ctx);
+        self.coop.start(self.lives);
+        self.push_turn_message(ctx);
+    }
+
+    fn push_turn_message(&mut self, ctx: &mut Context) {
+        self.messages.push_back(Message::new(
+            self.coop.current().to_string() + "'s Turn",
+            2000.0,
+            &self.assets,
+            ctx,
+        ));
+    }
+
+    // everyone's share is kept as their co-op best, then added up for the team
+    fn show_coop_results(&mut self, ctx: &mut Context) {
+        self.coop.finish(&mut self.score);
+        let mut lines = Vec::new();
+        let mut new_best = false;
+        for (name, score) in self.coop.players.iter().zip(&self.coop.scores) {
+            let mut line = format!(
+                "{}: {}  Accuracy {}%",
+                name,
+                score.points,
+                (score.accuracy() * 100.0).round() as i32
+            );
+            let old_best = self
+                .personal_bests
+                .best(Mode::CoOp, name, self.difficulty_selection);
+            if self
+                .personal_bests
+                .record(Mode::CoOp, name, self.difficulty_selection, score.points)
+            {
+                new_best = true;
+                if old_best.is_some() {
+                    line += "  New Best!";
+                }
+            }
+            lines.push(line);
+        }
+        if new_best {
+            self.personal_bests.save();
+        }
+        lines.push(format!("Team Score: {}", self.coop.team_score()));
+        self.text.results_title = MBText::new(
+            self.levels[self.current_level].title.clone(),
+            &self.assets.title_font,
+            BLUE,
+            128.0,
+            ctx,
+        );
+        self.text.results = lines
+            .into_iter()
+            .map(|line| MBText::new(line, &self.assets.main_font, WHITE, 48.0, ctx))
+            .collect();
+        self.state = GameState::Results;
+    }
+
+    fn set_lan_status(&mut self, ctx: &mut Context, status: String) {
+        self.text.lan_status = MBText::new(status, &self.assets.main_font, GRAY, 40.0, ctx);
+    }
+
+    fn update_lan_menu(&mut self, ctx: &mut Context) {
+        for lan_name in &mut self.text.lan_names {
+            lan_name.update(self.dt);
+        }
+        if let Some(action) = self.action {
+            if action == Action::Confirm && self.lan_selection == HOST {
+                match LanSession::host(DEFAULT_PORT, self.profiles.current_name()) {
+                    Ok(lan) => {
+                        self.lan = Some(lan);
+                        self.state = GameState::LanLobby;
+                    }
+                    Err(e) => self.set_lan_status(ctx, format!("Couldn't Host: {}", e)),
+                }
+            } else if action == Action::Confirm {
+                let address = self.lan_address.clone();
+                self.start_name_entry(ctx, NameFor::Address, address);
+            } else if action == Action::MenuDown {
+                self.lan_selection = (self.lan_selection + 1) % LAN_ITEMS.len();
+            } else if action == Action::MenuUp {
+                self.lan_selection = if self.lan_selection == 0 {
+                    LAN_ITEMS.len() - 1
+                } else {
+                    self.lan_selection - 1
+                };
+            }
+        }
+    }
+
+    // the host starts a match with enter, everyone else starts when it does
+    fn update_lan_lobby(&mut self, ctx: &mut Context) {
+        let lan = match &mut self.lan {
+            Some(lan) => lan,
+            None => return,
+        };
+        if lan.is_host() && self.action == Some(Action::Confirm) {
+            lan.start_match(rand::thread_rng().gen(), self.difficulty_selection);
+        } else if lan.new_match {
+            lan.new_match = false;
+        } else {
+            return;
+        }
+        self.lan_seed = lan.seed.unwrap_or(0);
+        self.lan_level = lan_level(self.lan_seed);
+        self.load_level_wave(0, 0);
+        self.start_playing(ctx);
+    }
+
+    // the network gets looked at every frame, whatever's on screen
+    fn update_lan(&mut self, ctx: &mut Context) {
+        let connected = match &mut self.lan {
+            Some(lan) => {
+                lan.update();
+                lan.connected()
+            }
+            None => return,
+        };
+        if !connected {
+            self.lan = None;
+            self.set_lan_status(ctx, "Lost Connection To The Host".to_string());
+            if self.state == GameState::LanLobby {
+                self.state = GameState::LanMenu;
+            }
+        }
+    }
+
+    // what the teacher's dashboard shows, sent a few times a second while
+    // it's running
+    fn publish_session(&mut self) {
+        let dashboard = match &self.dashboard {
+            Some(dashboard) => dashboard,
+            None => return,
+        };
+        self.dashboard_elapsed += self.dt.as_millis() as f32;
+        if self.dashboard_elapsed < PUBLISH_INTERVAL {
+            return;
+        }
+        self.dashboard_elapsed = 0.0;
+        // whoever's turn it is in co-op
+        let player = match self.mode {
+            Mode::CoOp => self.coop.players.get(self.coop.turn),
+            _ => None,
+        };
+        let player = player.map(|name| name.as_str());
+        let state = match self.state {
+            GameState::Playing | GameState::Sprint | GameState::Versus => "Playing",
+            GameState::LevelTransition(_) => "Starting A Wave",
+            GameState::Paused => "Paused",
+            GameState::Dying => "Lost A Gun",
+            GameState::Dead => "Game Over",
+            GameState::Won | GameState::LevelComplete | GameState::Results => "Looking At Results",
+            _ => "In The Menus",
+        };
+        let level = self.level();
+        dashboard.publish(SessionState {
+            player: player.unwrap_or(self.profiles.current_name()).to_string(),
+            mode: self.mode.name().to_string(),
+            state: state.to_string(),
+            level: level.title.clone(),
+            wave: self.current_wave + 1,
+            // endless never runs out, so there's no total to show
+            waves: if self.mode == Mode::Endless {
+                0
+            } else {
+                level.waves.len()
+            },
+            points: self.score.points,
+            lives: self.lives,
+            correct: self.score.correct,
+            wrong: self.score.wrong,
+            accuracy: self.score.accuracy(),
+            struggling: self.facts.struggling(),
+        });
+    }
+
+    fn report_lan_progress(&mut self, waves: usize) {
+        if let Some(lan) = &mut self.lan {
+            let name = lan.name.clone();
+            lan.report(LanMessage::Progress {
+                name: name.clone(),
+                wave: waves,
+            });
+            lan.report(LanMessage::Score {
+                name,
+                score: self.score.points,
+            });
+        }
+    }
+
+    fn report_lan_finish(&mut self, waves: usize) {
+        if let Some(lan) = &mut self.lan {
+            lan.report(LanMessage::Finish {
+                name: lan.name.clone(),
+                score: self.score.points,
+                waves,
+            });
+        }
+    }
+
+    fn finish_lan_run(&mut self, waves: usize, ctx: &mut Context) {
+        self.report_lan_finish(waves);
+        let mut lines = vec![format!("Waves Cleared: {} of {}", waves, DAILY_WAVES)];
+        if let Some(lan) = &self.lan {
+            let ranked = lan.leaderboard.ranked();
+            if let Some(place) = ranked.iter().position(|entry| entry.name == lan.name) {
+                lines.push(format!("Place So Far: {} of {}", place + 1, ranked.len()));
+            }
+        }
+        lines.extend(self.score.summary());
+        self.text.results_title = MBText::new(
+            self.lan_level.title.clone(),
+            &self.assets.title_font,
+            BLUE,
+            128.0,
+            ctx,
+        );
+        self.text.results = lines
+            .into_iter()
+            .map(|line| MBText::new(line, &self.assets.main_font, WHITE, 48.0, ctx))
+            .collect();
+        self.state = GameState::Results;
+    }
+
+    // a fresh match, both players start on the same first wave
+    fn start_versus(&mut self, ctx: &mut Context) {
+        self.versus_seed = rand::thread_rng().gen();
+        self.versus = versus_level(self.versus_seed);
+        self.current_wave = 0;
+        let mut sides = Vec::new();
+        for side in 0..PLAYER_NAMES.len() {
+            let turret = Turret::new_at(side_x(side, 0.5), &self.assets, ctx);
+            sides.push(Side::new(turret));
+        }
+        self.sides = sides;
+        for side in 0..self.sides.len() {
+            self.load_versus_wave(side);
+        }
+        let music = self.level().music().to_string();
+        self.assets.audio.play_music(ctx, &music, 1000.0);
+        self.assets.background =
+            graphics::Image::new(ctx, self.level().background_file.clone()).unwrap();
+        self.messages.clear();
+        self.messages.push_back(Message::new(
+            self.versus.title.clone(),
+            2000.0,
+            &self.assets,
+            ctx,
+        ));
+        self.state = GameState::Versus;
+    }
+
+    // each player goes back to the start of the wave they're on, lives and
+    // scores carry on so the rest of the match isn't lost
+    fn restart_versus_waves(&mut self, ctx: &mut Context) {
+        for side in 0..self.sides.len() {
+            self.sides[side].turret = Turret::new_at(side_x(side, 0.5), &self.assets, ctx);
+            self.load_versus_wave(side);
+        }
+        self.messages.clear();
+        self.messages.push_back(Message::new(
+            "Restarting Waves".to_string(),
+            2000.0,
+            &self.assets,
+            ctx,
+        ));
+        self.state = GameState::Versus;
+    }
+
+    fn load_versus_wave(&mut self, side: usize) {
+        let number = self.sides[side].wave;
+        let mut rng = versus_rng(self.versus_seed, number);
+        let wave = endless_wave(number, &mut rng);
+        let mut aliens = gen_aliens(&wave, &self.assets, self.difficulty_selection, &mut rng);
+        // squeeze the wave into this player's half
+        for alien in &mut aliens {
+            alien.pos[0] = side_x(side, alien.pos[0]);
+            alien.home_x = alien.pos[0];
+        }
+        let side = &mut self.sides[side];
+        side.target = get_lowest_living_alien(&aliens);
+        side.aliens = aliens;
+        side.score.reset_timer();
+    }
+
+    // a streak drops one more alien in just above the other player's half
+    fn send_alien(&mut self, ctx: &mut Context, to: usize) {
+        let mut rng = rand::thread_rng();
+        let wave = endless_wave(self.sides[to].wave, &mut rng);
+        let aliens = gen_aliens(&wave, &self.assets, self.difficulty_selection, &mut rng);
+        if let Some(mut alien) = aliens.into_iter().next() {
+            alien.pos = na::Point2::new(side_x(to, rng.gen_range(0.1, 0.9)), -0.05);
+            alien.home_x = alien.pos[0];
+            self.sides[to].aliens.push(alien);
+            self.messages.push_back(Message::new(
+                PLAYER_NAMES[1 - to].to_string() + " Sent An Alien!",
+                1500.0,
+                &self.assets,
+                ctx,
+            ));
+        }
+    }
+
+    fn press_versus_key(&mut self, ctx: &mut Context, side: usize, key: VersusKey) {
+        let player = &mut self.sides[side];
+        match key {
+            VersusKey::Char(ch) => player.turret.raw_text.push(ch),
+            VersusKey::Back => {
+                let _ = player.turret.raw_text.pop();
+            }
+            VersusKey::NextTarget => {
+                let next = match player.target {
+                    Some(target) => next_alien_across(&player.aliens, target, 1.0),
+                    None => get_lowest_living_alien(&player.aliens),
+                };
+                if next.is_some() {
+                    player.target = next;
+                }
+            }
+            VersusKey::Fire => self.fire_versus(ctx, side),
+        }
+        let turret = &mut self.sides[side].turret;
+        turret.text = MBText::new(
+            turret.raw_text.clone(),
+            &self.assets.number_font,
+            WHITE,
+            24.0,
+            ctx,
+        );
+    }
+
+    fn fire_versus(&mut self, ctx: &mut Context, side: usize) {
+        let player = &mut self.sides[side];
+        if player.turret.raw_text.is_empty() {
+            return;
+        }
+        let answer = &player.turret.raw_text;
+        let hit = player.target.filter(|i| {
+            let alien = &player.aliens[*i];
+            alien.state == AlienState::Alive && alien.problem.answer.matches(answer)
+        });
+        player.turret.raw_text.clear();
+        match hit {
+            Some(i) => {
+                let alien = &player.aliens[i];
+                player
+                    .score
+                    .hit(alien.operation, self.difficulty_selection, alien.pos[1]);
+                player.aliens[i].take_hit();
+                player.turret.state = TurretState::Firing;
+                self.assets.audio.play(Sound::Explosion);
+                if player.score.streak % SEND_STREAK == 0 {
+                    self.send_alien(ctx, 1 - side);
+                }
+            }
+            None => {
+                player.score.miss();
+                self.assets.audio.play(Sound::Fail);
+            }
+        }
+    }
+
+    // both halves play out side by side until either player is out of guns
+    fn update_versus(&mut self, ctx: &mut Context) {
+        self.background.update(self.dt, 1.0);
+        self.crosshair.update(self.dt);
+        self.update_messages();
+        let dt = self.dt;
+        let mut cleared = Vec::new();
+        for (i, side) in self.sides.iter_mut().enumerate() {
+            side.score.update(dt, side.target);
+            for alien in &mut side.aliens {
+                alien.update(&mut side.turret, ctx, dt);
+                // an alien that gets through costs a gun instead of the whole wave
+                if alien.state == AlienState::Alive && alien.pos[1] > 0.9 {
+                    alien.state = AlienState::Exploding;
+                    side.lives = side.lives.saturating_sub(1);
+                    side.score.streak = 0;
+                    self.assets.audio.play(Sound::Explosion);
+                }
+            }
+            match side.target {
+                Some(target) if side.aliens[target].state != AlienState::Dead => {
+                    side.turret.aim_at(side.aliens[target].pct_pos());
+                }
+                _ => side.target = get_lowest_living_alien(&side.aliens),
+            }
+            if side
+                .aliens
+                .iter()
+                .all(|alien| alien.state == AlienState::Dead)
+            {
+                cleared.push(i);
+            }
+        }
+        for side in cleared {
+            self.sides[side].wave += 1;
+            self.load_versus_wave(side);
+            self.messages.push_back(Message::new(
+                format!("{}: Wave {}", PLAYER_NAMES[side], self.sides[side].wave + 1),
+                2000.0,
+                &self.assets,
+                ctx,
+            ));
+        }
+        if self.sides.iter().any(|side| side.lives == 0) {
+            self.show_versus_results(ctx);
+        }
+    }
+
+    fn show_versus_results(&mut self, ctx: &mut Context) {
+        self.assets.audio.play(Sound::Clap);
+        self.messages.clear();
+        // whoever still has guns wins, if both ran out together the score decides
+        let standings: Vec<(bool, u32)> = self
+            .sides
+            .iter()
+            .map(|side| (side.lives > 0, side.score.points))
+            .collect();
+        let title = if standings[0] == standings[1] {
+            "Draw".to_string()
+        } else if standings[0] > standings[1] {
+            PLAYER_NAMES[0].to_string() + " Wins!"
+        } else {
+            PLAYER_NAMES[1].to_string() + " Wins!"
+        };
+        self.text.results_title = MBText::new(title, &self.assets.title_font, BLUE, 128.0, ctx);
+        self.text.results = self
+            .sides
+            .iter()
+            .enumerate()
+            .map(|(i, side)| {
+                format!(
+                    "{}: {}  Wave {}  Accuracy {}%",
+                    PLAYER_NAMES[i],
+                    side.score.points,
+                    side.wave + 1,
+                    (side.score.accuracy() * 100.0).round() as i32
+                )
+            })
+            .map(|line| MBText::new(line, &self.assets.main_font, WHITE, 48.0, ctx))
+            .collect();
+        self.state = GameState::Results;
+    }
+
+    // clicking an alien targets it for whoever's half it's on
+    fn click_versus(&mut self, ctx: &mut Context, pos: na::Point2<f32>) {
+        let screen = graphics::size(ctx);
+        for side in &mut self.sides {
+            if let Some(i) = side.aliens.iter().position(|alien| {
+                alien.state == AlienState::Alive && alien.pos[1] >= 0.0 && alien.hit(pos, screen)
+            }) {
+                side.target = Some(i);
+            }
+        }
+    }
+
+    fn resume(&mut self) {
+        self.state = match self.mode {
+            Mode::MadMinute => GameState::Sprint,
+            Mode::Versus => GameState::Versus,
+            _ => GameState::Playing,
+        };
+    }
+
+    // zen practice lets a profile play any level without losing
+    fn zen(&self) -> bool {
+        self.mode == Mode::Campaign && self.profiles.zen()
+    }
+
+    fn level_open(&self, level: usize) -> bool {
+        self.zen() || self.levels[level].unlocked[self.difficulty_selection]
+    }
+
+    // locked levels can sit between unlocked ones, so step over them
+    fn move_level_selection(&mut self, forward: bool) {
+        let count = self.levels.len();
+        for _ in 0..count {
+            self.level_selection = if forward {
+                (self.level_selection + 1) % count
+            } else {
+                (self.level_selection + count - 1) % count
+            };
+            if self.level_open(self.level_selection) {
+                break;
+            }
+        }
+    }
+
+    fn update_level_select(&mut self, ctx: &mut Context) {
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                self.load_level_wave(self.level_selection, 0);
+                self.start_playing(ctx);
+            } else if action == Action::MenuDown {
+                self.move_level_selection(true);
+            } else if action == Action::MenuUp {
+                self.move_level_selection(false);
+            } else if action == Action::TargetNext || action == Action::TargetPrev {
+                self.high_score_profile = self.profiles.current;
+                self.show_high_scores(ctx);
+            }
+        }
+        for level_name in &mut self.text.level_names {
+            level_name.update(self.dt)
+        }
+    }
+    fn show_results(&mut self, ctx: &mut Context) {
+        self.text.results_title = MBText::new(
+            self.levels[self.current_level].title.clone(),
+            &self.assets.title_font,
+            BLUE,
+            128.0,
+            ctx,
+        );
+        self.text.results = self
+            .score
+            .summary()
+            .into_iter()
+            .map(|line| MBText::new(line, &self.assets.main_font, WHITE, 64.0, ctx))
+            .collect();
+        self.state = GameState::Results;
+    }
+
+    fn update_results(&mut self, ctx: &mut Context) {
+        self.background.update(self.dt, 1.0);
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                if self.mode != Mode::Campaign {
+                    self.quit_to_menu();
+                } else if self.zen() {
+                    self.finish_level();
+                } else if self.high_scores.qualifies(
+                    &self.levels[self.current_level].title,
+                    self.difficulty_selection,
+                    self.profiles.current_name(),
+                    self.score.points,
+                ) {
+                    let name = self.profiles.current_name().to_string();
+                    self.start_name_entry(ctx, NameFor::HighScore, name);
+                } else {
+                    self.finish_level();
+                }
+            }
+        }
+    }
+
+    // on to the next level once the results have been seen
+    fn finish_level(&mut self) {
+        if self.current_level + 1 >= self.levels.len() {
+            self.state = GameState::Won;
+        } else if !self.level_open(self.current_level + 1) {
+            // the next level wants more stars first
+            self.level_selection = self.current_level;
+            self.state = GameState::LevelSelect;
+        } else {
+            self.set_level_wave(self.current_level + 1, 0);
+        }
+    }
+
+    fn update_won(&mut self, _ctx: &mut Context) {
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                self.state = GameState::DifficultySelect;
+            }
+        }
+    }
+    fn update_level_complete(&mut self, ctx: &mut Context) {
+        self.turret.rotation = 0.0;
+        self.background.update(self.dt, 1.0);
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                self.assets.audio.play(Sound::Launch);
+                // the next level is already loaded, so fade to its music on the way
+                let music = self.levels[self.current_level].music().to_string();
+                self.assets.audio.play_music(ctx, &music, 3000.0);
+                self.state = GameState::LevelTransition(0.0);
+                self.levels[self.current_level].push_title(&mut self.messages, &self.assets, ctx);
+                self.messages.push_back(Message::new(
+                    "WARP SPEED".to_string(),
+                    2000.0,
+                    &self.assets,
+                    ctx,
+                ));
+            }
+        }
+    }
+    fn update_level_transition(&mut self, ctx: &mut Context, elapsed: f32) {
+        self.state = GameState::LevelTransition(elapsed + self.dt.as_millis() as f32);
+        let pct = elapsed / 3000.0;
+        self.background.update(self.dt, 1.0 + pct * 1.0);
+        self.turret.pos[1] -= 0.015 * pct;
+
+        if elapsed >= 3000.0 {
+            self.state = GameState::Playing;
+            self.score = Score::new(self.lives);
+            self.levels[self.current_level].push_title(&mut self.messages, &self.assets, ctx);
+            self.messages.push_back(Message::new(
+                "Wave 1".to_string(),
+                2000.0,
+                &self.assets,
+                ctx,
+            ));
+            self.assets.background =
+                graphics::Image::new(ctx, self.levels[self.current_level].background_file.clone())
+                    .unwrap();
+            self.turret.pos = na::Point2::new(0.5, 0.9); //put turret back at the bottom
+        }
+    }
+
+    fn update_dead(&mut self, _ctx: &mut Context) {
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                self.quit_to_menu();
+            }
+        }
+    }
+
+    fn update_dying(&mut self, ctx: &mut Context) {
+        for alien in &mut self.aliens {
+            alien.update(&mut self.turret, ctx, self.dt);
+        }
+        self.turret.update(ctx, self.dt);
+        for splosion in &mut self.turret.explosions {
+            splosion.update(ctx, self.dt);
+        }
+        if self
+            .turret
             .explosions
             .iter()
             .all(|splosion| splosion.elapsed - splosion.start_time > splosion.duration)
         {
             if self.lives > 0 {
                 self.lives -= 1;
+                self.score.streak = 0;
                 self.turret = Turret::new(&self.assets, ctx);
                 self.set_level_wave(self.current_level, self.current_wave);
                 self.state = GameState::Playing;
@@ -475,167 +2242,680 @@ impl MainState {
                     &self.assets,
                     ctx,
                 ));
+            } else if self.mode == Mode::Endless {
+                self.show_endless_results(ctx);
+            } else if self.mode == Mode::Daily {
+                // the wave that ended the run doesn't count
+                self.show_daily_results(self.current_wave, ctx);
+            } else if self.mode == Mode::CoOp {
+                self.show_coop_results(ctx);
+            } else if self.mode == Mode::Lan {
+                self.finish_lan_run(self.current_wave, ctx);
+            } else {
+                self.state = GameState::Dead;
+            }
+        }
+    }
+
+    // explode the target on a correct answer, otherwise play the fail sound
+    fn resolve_answer(&mut self, ctx: &mut Context, correct: bool) {
+        let aliens = &self.aliens;
+        if let Some(alien) = self.target.and_then(|i| aliens.get(i)) {
+            let answer = alien.problem.answer.to_string();
+            self.facts.record(&alien.problem.text, &answer, correct);
+        }
+        match self.target {
+            Some(alien_index) if correct => {
+                let alien = &self.aliens[alien_index];
+                self.score
+                    .hit(alien.operation, self.difficulty(), alien.pos[1]);
+                if !self.aliens[alien_index].take_hit() {
+                    // a boss on to its next problem, which gets read out
+                    // and counted for zen practice afresh
+                    self.spoken_target = None;
+                    self.zen_misses = (None, 0);
+                }
+                self.assets.audio.play(Sound::Explosion);
+                self.turret.state = TurretState::Firing;
+                if self.settings.voice {
+                    self.assets.audio.speak(correct_words());
+                }
+            }
+            _ => {
+                self.score.miss();
+                self.assets.audio.play(Sound::Fail);
+                if self.settings.voice {
+                    self.assets.audio.speak(try_again_words());
+                }
+                if self.zen() {
+                    self.count_zen_miss(ctx);
+                }
+            }
+        }
+    }
+
+    fn count_zen_miss(&mut self, ctx: &mut Context) {
+        let alien_index = match self.target {
+            Some(alien_index) => alien_index,
+            None => return,
+        };
+        if self.zen_misses.0 != self.target {
+            self.zen_misses = (self.target, 0);
+        }
+        self.zen_misses.1 += 1;
+        if self.zen_misses.1 < ZEN_ATTEMPTS {
+            return;
+        }
+        self.zen_misses.1 = 0;
+        let alien = &self.aliens[alien_index];
+        let answer = alien.problem.answer.to_string();
+        let solution = match alien.operation {
+            Operation::WordProblem => format!("Answer: {}", answer),
+            Operation::Compare => alien.problem.text.replace('□', &answer),
+            _ => format!("{} = {}", alien.problem.text, answer),
+        };
+        self.messages.clear();
+        self.messages
+            .push_back(Message::numbers(solution, 3000.0, &self.assets, ctx));
+    }
+
+    fn choose_answer(&mut self, ctx: &mut Context, index: usize) {
+        if let Some(alien_index) = self.target {
+            let alien = &self.aliens[alien_index];
+            if alien.state != AlienState::Alive {
+                return;
+            }
+            if let Some(choice) = alien.choices.get(index) {
+                log_choice(
+                    self.profiles.current_name(),
+                    &self.level().title,
+                    self.current_wave,
+                    &alien.problem.text,
+                    &alien.problem.answer.to_string(),
+                    choice,
+                );
+                let correct = choice.misconception.is_none();
+                self.resolve_answer(ctx, correct);
+            }
+        }
+    }
+
+    fn escape(&mut self, ctx: &mut Context) {
+        match self.state {
+            GameState::ModeSelect | GameState::ProfileSelect => {
+                self.state = GameState::DifficultySelect
+            }
+            GameState::SprintSetup
+            | GameState::DailyCalendar
+            | GameState::CoOpSetup
+            | GameState::LanMenu => self.state = GameState::ModeSelect,
+            // leaving the lobby leaves the match, or ends it for everyone if hosting
+            GameState::LanLobby => {
+                self.lan = None;
+                self.set_lan_status(ctx, String::new());
+                self.state = GameState::LanMenu;
+            }
+            GameState::NameEntry(NameFor::Address) => self.state = GameState::LanMenu,
+            GameState::LevelSelect => self.state = GameState::ModeSelect,
+            GameState::NameEntry(NameFor::Profile) => self.state = GameState::ProfileSelect,
+            // skipping the name leaves the score off the table
+            GameState::NameEntry(NameFor::HighScore) => self.finish_level(),
+            GameState::HighScores => self.state = GameState::LevelSelect,
+            GameState::KeyBindings => self.state = GameState::Settings,
+            GameState::Settings if self.settings_from_pause => self.state = GameState::Paused,
+            GameState::Settings => self.state = GameState::DifficultySelect,
+            GameState::DifficultySelect => event::quit(ctx),
+            GameState::Playing | GameState::Sprint | GameState::Versus => self.pause(),
+            GameState::Paused => self.resume(),
+            _ => self.quit_to_menu(),
+        }
+    }
+
+    fn pause(&mut self) {
+        self.pause_selection = RESUME;
+        self.state = GameState::Paused;
+    }
+
+    // nothing moves while paused, only the menu gets updated
+    fn update_paused(&mut self, ctx: &mut Context) {
+        for pause_name in &mut self.text.pause_names {
+            pause_name.update(self.dt);
+        }
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                match self.pause_selection {
+                    RESUME => self.resume(),
+                    RESTART_WAVE if self.mode == Mode::MadMinute => self.start_sprint(ctx),
+                    RESTART_WAVE if self.mode == Mode::Versus => self.restart_versus_waves(ctx),
+                    RESTART_WAVE => {
+                        self.turret = Turret::new(&self.assets, ctx);
+                        self.load_level_wave(self.current_level, self.current_wave);
+                        self.messages.clear();
+                        self.messages.push_back(Message::new(
+                            "Restarting Wave ".to_string() + &(self.current_wave + 1).to_string(),
+                            2000.0,
+                            &self.assets,
+                            ctx,
+                        ));
+                        self.state = GameState::Playing;
+                    }
+                    SETTINGS => {
+                        self.settings_from_pause = true;
+                        self.settings_selection = 0;
+                        self.state = GameState::Settings;
+                    }
+                    _ => {
+                        self.messages.clear();
+                        // everyone else sees the run end where it was left
+                        if self.mode == Mode::Lan {
+                            self.report_lan_finish(self.current_wave);
+                        }
+                        self.quit_to_menu();
+                    }
+                }
+            } else if action == Action::MenuDown {
+                self.pause_selection = (self.pause_selection + 1) % PAUSE_ITEMS.len();
+            } else if action == Action::MenuUp {
+                self.pause_selection = if self.pause_selection == 0 {
+                    PAUSE_ITEMS.len() - 1
+                } else {
+                    self.pause_selection - 1
+                };
+            }
+        }
+    }
+
+    // digits pick a choice when answering by multiple choice
+    fn input_char(&mut self, ctx: &mut Context, ch: char) {
+        if self.settings.input_mode == InputMode::MultipleChoice {
+            if let Some(digit) = ch.to_digit(10) {
+                if digit >= 1 {
+                    self.choose_answer(ctx, digit as usize - 1);
+                }
+            }
+        } else {
+            self.type_char(ctx, ch);
+        }
+    }
+
+    fn type_char(&mut self, ctx: &mut Context, ch: char) {
+        if self.level().waves[self.current_wave].accepts_input(ch) {
+            self.turret.raw_text += &ch.to_string();
+            self.turret.text = MBText::new(
+                self.turret.raw_text.clone(),
+                &self.assets.number_font,
+                WHITE,
+                24.0,
+                ctx,
+            );
+        }
+    }
+
+    // keys from the on screen keypad and the gamepad picker
+    fn press_key(&mut self, ctx: &mut Context, key: Key) {
+        match key {
+            Key::Char(ch) => self.input_char(ctx, ch),
+            Key::Back => self.action = Some(Action::Back),
+            Key::Enter => self.action = Some(Action::Confirm),
+        }
+    }
+
+    fn click_playing(&mut self, ctx: &mut Context, pos: na::Point2<f32>) {
+        let screen = graphics::size(ctx);
+        if let Some(key) = self.keypad.key_at(pos, screen) {
+            self.press_key(ctx, key);
+            return;
+        }
+        // a tap means there's a touchscreen, so bring up the keypad
+        if self.settings.input_mode == InputMode::Typed {
+            self.keypad.visible = true;
+        }
+
+        if self.settings.input_mode == InputMode::MultipleChoice {
+            if let Some(alien_index) = self.target {
+                let alien = &self.aliens[alien_index];
+                let count = alien.choices.len();
+                if let Some(index) = (0..count)
+                    .find(|i| choice_rect(*i, count, alien.choices_pos(), screen).contains(pos))
+                {
+                    self.choose_answer(ctx, index);
+                    return;
+                }
+            }
+        }
+
+        if let Some(alien_index) = self.aliens.iter().position(|alien| {
+            alien.state == AlienState::Alive && alien.pos[1] >= 0.0 && alien.hit(pos, screen)
+        }) {
+            self.target = Some(alien_index);
+        }
+    }
+
+    fn update_playing(&mut self, ctx: &mut Context) {
+        self.update_field(ctx);
+        if self.zen() {
+            // aliens wait above the turret for an answer instead of landing
+            for alien in &mut self.aliens {
+                alien.pos[1] = alien.pos[1].min(ZEN_FLOOR);
+            }
+        }
+
+        // Find the alien furthest down the screen, if its at the bottom, dead.
+        match self
+            .aliens
+            .iter()
+            .max_by_key(|alien| (alien.pos[1] * 1000.0) as i32)
+        {
+            Some(alien) => {
+                if alien.pos[1] > 0.9 {
+                    self.state = GameState::Dying
+                };
+            }
+            None => (),
+        }
+
+        //If all aliens are dead, increment the wave/level
+        if self
+            .aliens
+            .iter()
+            .all(|alien| alien.state == AlienState::Dead)
+        {
+            self.increment_level_wave(ctx);
+        }
+    }
+
+    fn update_messages(&mut self) {
+        if !self.messages.is_empty() {
+            self.messages[0].update(self.dt);
+            if self.messages[0].elapsed >= self.messages[0].duration {
+                let _ = self.messages.pop_front();
+            }
+        }
+    }
+
+    // answering, targeting and moving everything, whatever mode is being played
+    fn update_field(&mut self, ctx: &mut Context) {
+        self.background.update(self.dt, 1.0);
+        self.crosshair.update(self.dt);
+        if let Some(action) = self.action {
+            if action == Action::Confirm {
+                if !self.turret.raw_text.is_empty() {
+                    let correct = match self.target {
+                        Some(alien_index) => self.aliens[alien_index]
+                            .problem
+                            .answer
+                            .matches(&self.turret.raw_text),
+                        None => false,
+                    };
+                    self.resolve_answer(ctx, correct);
+                }
+                self.turret.raw_text = "".to_string();
+                self.turret.text = MBText::new(
+                    self.turret.raw_text.clone(),
+                    &self.assets.number_font,
+                    WHITE,
+                    24.0,
+                    ctx,
+                );
+            } else if action == Action::Back {
+                let _ = self.turret.raw_text.pop();
+                self.turret.text = MBText::new(
+                    self.turret.raw_text.clone(),
+                    &self.assets.number_font,
+                    WHITE,
+                    24.0,
+                    ctx,
+                );
+            } else if action == Action::TargetPrev || action == Action::TargetNext {
+                let direction = if action == Action::TargetPrev {
+                    -1.0
+                } else {
+                    1.0
+                };
+                if let Some(index) = self.target {
+                    if let Some(i) = next_alien_across(&self.aliens, index, direction) {
+                        self.target = Some(i);
+                    }
+                }
+            }
+        }
+
+        self.score.update(self.dt, self.target);
+
+        //update aliens and turret, and message queue
+        for alien in &mut self.aliens {
+            alien.update(&mut self.turret, ctx, self.dt);
+        }
+        self.turret.update(ctx, self.dt);
+        self.update_messages();
+
+        // If there is a target, rotate the turret to it
+        match self.target {
+            Some(target) if self.aliens[target].state != AlienState::Dead => {
+                self.turret.aim_at(self.aliens[target].pct_pos());
+            }
+            Some(_) => self.target = get_lowest_living_alien(&self.aliens),
+            None => (),
+        };
+
+        // read the problem out whenever a new alien is targeted, once it's on screen
+        if self.settings.voice && self.target != self.spoken_target {
+            if let Some(alien_index) = self.target {
+                let alien = &self.aliens[alien_index];
+                if alien.state == AlienState::Alive && alien.pos[1] >= 0.0 {
+                    self.assets.audio.interrupt();
+                    self.assets.audio.speak(spoken_words(&alien.problem.text));
+                    self.spoken_target = self.target;
+                }
+            }
+        }
+    }
+    fn draw_difficulty_select(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let mut title_pos = self.text.math_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.math_title.draw(title_pos, ctx);
+
+        let positions = menu_positions(&self.text.difficulty_names, ctx);
+        for (i, difficulty_name) in self.text.difficulty_names.iter().enumerate() {
+            if i == self.difficulty_selection {
+                difficulty_name.draw(positions[i], ctx);
+            } else {
+                difficulty_name.draw_color(positions[i], GRAY, ctx);
+            }
+        }
+
+        let input_mode_pos = self.input_mode_pos(ctx);
+        self.input_mode_name().draw(input_mode_pos, ctx);
+
+        let settings_pos = self.settings_pos(ctx);
+        if self.difficulty_selection == DIFFICULTY_NAMES.len() {
+            self.text.settings.draw(settings_pos, ctx);
+        } else {
+            self.text.settings.draw_color(settings_pos, GRAY, ctx);
+        }
+
+        let player_pos = self.player_pos(ctx);
+        if self.difficulty_selection == DIFFICULTY_NAMES.len() + 1 {
+            self.text.player.draw(player_pos, ctx);
+        } else {
+            self.text.player.draw_color(player_pos, GRAY, ctx);
+        }
+    }
+
+    fn input_mode_name(&self) -> &MBText {
+        let input_mode = INPUT_MODES
+            .iter()
+            .position(|mode| *mode == self.settings.input_mode)
+            .unwrap_or(0);
+        &self.text.input_mode_names[input_mode]
+    }
+
+    // the answer mode toggle sits a little below the difficulty list
+    fn input_mode_pos(&self, ctx: &mut Context) -> na::Point2<f32> {
+        let window_dimension = graphics::size(ctx);
+        let last = self.text.difficulty_names.len() - 1;
+        let last_pos = menu_positions(&self.text.difficulty_names, ctx)[last];
+        let last_height = self.text.difficulty_names[last]
+            .dest_pixel_dimensions(window_dimension)
+            .1;
+        let mut pos = self.input_mode_name().center(ctx);
+        pos[1] = last_pos[1] + last_height * 1.075 + 0.05 * window_dimension.1;
+        pos
+    }
+
+    fn settings_pos(&self, ctx: &mut Context) -> na::Point2<f32> {
+        let window_dimension = graphics::size(ctx);
+        let input_mode_pos = self.input_mode_pos(ctx);
+        let input_mode_height = self
+            .input_mode_name()
+            .dest_pixel_dimensions(window_dimension)
+            .1;
+        let mut pos = self.text.settings.center(ctx);
+        pos[1] = input_mode_pos[1] + input_mode_height * 1.075;
+        pos
+    }
+
+    fn player_pos(&self, ctx: &mut Context) -> na::Point2<f32> {
+        let window_dimension = graphics::size(ctx);
+        let settings_pos = self.settings_pos(ctx);
+        let settings_height = self.text.settings.dest_pixel_dimensions(window_dimension).1;
+        let mut pos = self.text.player.center(ctx);
+        pos[1] = settings_pos[1] + settings_height * 1.075;
+        pos
+    }
+
+    fn draw_mode_select(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let mut title_pos = self.text.math_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.math_title.draw(title_pos, ctx);
+
+        let positions = menu_positions(&self.text.mode_names, ctx);
+        for (i, mode_name) in self.text.mode_names.iter().enumerate() {
+            if i == self.mode_selection {
+                mode_name.draw(positions[i], ctx);
+            } else {
+                mode_name.draw_color(positions[i], GRAY, ctx);
+            }
+        }
+    }
+
+    fn draw_sprint_setup(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let mut title_pos = self.text.math_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.math_title.draw(title_pos, ctx);
+
+        let positions = menu_positions(&self.text.sprint_names, ctx);
+        for (i, sprint_name) in self.text.sprint_names.iter().enumerate() {
+            if i == self.sprint_selection {
+                sprint_name.draw(positions[i], ctx);
+            } else {
+                sprint_name.draw_color(positions[i], GRAY, ctx);
+            }
+        }
+    }
+
+    fn draw_lan_menu(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let mut title_pos = self.text.math_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.math_title.draw(title_pos, ctx);
+
+        let positions = menu_positions(&self.text.lan_names, ctx);
+        for (i, lan_name) in self.text.lan_names.iter().enumerate() {
+            if i == self.lan_selection {
+                lan_name.draw(positions[i], ctx);
+            } else {
+                lan_name.draw_color(positions[i], GRAY, ctx);
+            }
+        }
+        let window_dimension = graphics::size(ctx);
+        self.text
+            .lan_status
+            .draw_horizontal_center(0.8 * window_dimension.1, ctx);
+    }
+
+    fn draw_lan_lobby(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let mut title_pos = self.text.math_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.math_title.draw(title_pos, ctx);
+        self.draw_leaderboard(0.3, 0.35, 48.0, ctx);
+        let window_dimension = graphics::size(ctx);
+        match &self.lan {
+            Some(lan) if lan.is_host() => self
+                .text
+                .press_enter
+                .draw_horizontal_center(0.9 * window_dimension.1, ctx),
+            _ => self
+                .text
+                .lan_status
+                .draw_horizontal_center(0.9 * window_dimension.1, ctx),
+        }
+    }
+
+    fn draw_coop_setup(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let mut title_pos = self.text.math_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.math_title.draw(title_pos, ctx);
+
+        let positions = menu_positions(&self.text.coop_names, ctx);
+        for (i, coop_name) in self.text.coop_names.iter().enumerate() {
+            if i == self.coop_selection {
+                coop_name.draw(positions[i], ctx);
             } else {
-                self.state = GameState::Dead;
+                coop_name.draw_color(positions[i], GRAY, ctx);
             }
         }
     }
 
-    fn update_playing(&mut self, ctx: &mut Context) {
-        self.background.update(self.dt, 1.0);
-        self.crosshair.update(self.dt);
-        if let Some(keycode) = self.up_key {
-            if keycode == KeyCode::Return {
-                match self.turret.raw_text.parse::<i32>() {
-                    Ok(n) => match self.target {
-                        Some(alien_index) if self.aliens[alien_index].answer == n => {
-                            self.aliens[alien_index].state = AlienState::Exploding;
-                            let _ = self.assets.explosion_sound.play_detached();
-                            self.turret.state = TurretState::Firing;
-                        }
-                        _ => {
-                            let _ = self.assets.fail_sound.play_detached();
-                        }
-                    },
-                    Err(_) => (),
-                }
-                self.turret.raw_text = "".to_string();
-                self.turret.text = MBText::new(
-                    self.turret.raw_text.clone(),
-                    &self.assets.number_font,
-                    WHITE,
-                    24.0,
-                    ctx,
+    // a month of days, each with the score from that day's challenge if
+    // this profile played it
+    fn draw_daily_calendar(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let screen = graphics::size(ctx);
+        let scale = screen.0 / 1920.0;
+        let font = self.assets.main_font;
+        let draw_text =
+            |text: String, size: f32, center: (f32, f32), color: Color, ctx: &mut Context| {
+                let text = graphics::Text::new((text, font, size));
+                let (tw, th) = text.dimensions(ctx);
+                let dest = na::Point2::new(
+                    center.0 - tw as f32 * scale / 2.0,
+                    center.1 - th as f32 * scale / 2.0,
                 );
-            } else if keycode == KeyCode::Back {
-                let _ = self.turret.raw_text.pop();
-                self.turret.text = MBText::new(
-                    self.turret.raw_text.clone(),
-                    &self.assets.number_font,
-                    WHITE,
-                    24.0,
+                let _ = graphics::draw(
                     ctx,
+                    &text,
+                    graphics::DrawParam::new()
+                        .color(color)
+                        .dest(dest)
+                        .scale(na::Vector2::new(scale, scale)),
                 );
-            } else if keycode == KeyCode::Left {
-                match self.target {
-                    Some(index) => {
-                        if self
-                            .aliens
-                            .iter()
-                            .any(|alien| alien.state == AlienState::Alive && alien.pos[1] >= 0.0)
-                        {
-                            let mut i = if index == 0 {
-                                self.aliens.len() - 1
-                            } else {
-                                index - 1
-                            };
-                            while self.aliens[i].state != AlienState::Alive
-                                || self.aliens[i].pos[1] < 0.0
-                            {
-                                i = if i == 0 { self.aliens.len() - 1 } else { i - 1 }
-                            }
-                            self.target = Some(i);
-                        }
-                    }
-                    _ => (),
-                }
-            } else if keycode == KeyCode::Right {                
-                match self.target {
-                    Some(index) => {
-                        if self
-                            .aliens
-                            .iter()
-                            .any(|alien| alien.state == AlienState::Alive && alien.pos[1] > 0.0)
-                        {
-                            let mut i = (index + 1) % self.aliens.len();
-                            while self.aliens[i].state != AlienState::Alive
-                                || self.aliens[i].pos[1] < 0.0
-                            {
-                                i = (i + 1) % self.aliens.len();
-                            }
-                            self.target = Some(i);
-                        }
-                    }
+            };
 
-                    _ => (),
-                }
-            }
-        }
+        let month = self.calendar_month.format("< %B %Y >").to_string();
+        draw_text(month, 64.0, (0.5 * screen.0, 0.12 * screen.1), WHITE, ctx);
 
-        //update aliens and turret, and message queue
-        for alien in &mut self.aliens {
-            alien.update(&mut self.turret, ctx, self.dt);
+        let cell = (0.1 * screen.0, 0.1 * screen.1);
+        let left = 0.5 * screen.0 - 3.0 * cell.0;
+        let top = 0.25 * screen.1;
+        for (i, day) in ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+            .iter()
+            .enumerate()
+        {
+            let x = left + i as f32 * cell.0;
+            draw_text(day.to_string(), 32.0, (x, top), GRAY, ctx);
         }
-        self.turret.update(ctx, self.dt);
-        if !self.messages.is_empty() {
-            self.messages[0].update(self.dt);
-            if self.messages[0].elapsed >= self.messages[0].duration {
-                let _ = self.messages.pop_front();
+
+        let today = today();
+        let profile = self.profiles.current_name();
+        let offset = self.calendar_month.weekday().num_days_from_monday() as usize;
+        let mut date = self.calendar_month;
+        while date.month() == self.calendar_month.month() {
+            let slot = offset + date.day0() as usize;
+            let x = left + (slot % 7) as f32 * cell.0;
+            let y = top + (slot / 7 + 1) as f32 * cell.1;
+            let color = if date == today { BLUE } else { WHITE };
+            draw_text(
+                date.day().to_string(),
+                40.0,
+                (x, y - 0.015 * screen.1),
+                color,
+                ctx,
+            );
+            if let Some(result) = self.daily_history.result(date, profile) {
+                let score = result.score.to_string();
+                draw_text(score, 28.0, (x, y + 0.025 * screen.1), GRAY, ctx);
             }
+            date = match date.succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
         }
 
-        // If there is a target, rotate the turret to it
-        match self.target {
-            Some(target) if self.aliens[target].state != AlienState::Dead => {
-                let turret_pos = self.turret.pct_pos();
-                let turret_vector: na::Vector2<f32> =
-                    na::Vector2::new(turret_pos[0], turret_pos[1]);
-                let alien_pos = self.aliens[target].pct_pos();
-                let alien_vector = na::Vector2::new(alien_pos[0], alien_pos[1]);
-                let v1 = na::Vector2::new(0.0, -1.0);
-                let v2 = alien_vector - turret_vector;
-                let mut angle = v2.angle(&v1);
-                if alien_pos[0] < 0.5 {
-                    angle = -angle;
-                }
-                self.turret.rotation = angle;
-            }
-            Some(_) => self.target = get_lowest_living_alien(&self.aliens),
-            None => (),
-        };
+        self.text
+            .press_enter
+            .draw_horizontal_center(0.9 * screen.1, ctx);
+    }
 
-        // Find the alien furthest down the screen, if its at the bottom, dead.
-        match self
-            .aliens
-            .iter()
-            .max_by_key(|alien| (alien.pos[1] * 1000.0) as i32)
-        {
-            Some(alien) => {
-                if alien.pos[1] > 0.9 {                    
-                    self.state = GameState::Dying
-                };
+    fn draw_profile_select(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let mut title_pos = self.text.math_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.math_title.draw(title_pos, ctx);
+
+        let positions = menu_positions(&self.text.profile_names, ctx);
+        for (i, profile_name) in self.text.profile_names.iter().enumerate() {
+            if i == self.profile_selection {
+                profile_name.draw(positions[i], ctx);
+            } else {
+                profile_name.draw_color(positions[i], GRAY, ctx);
             }
-            None => (),
         }
+    }
 
-        //If all aliens are dead, increment the wave/level
-        if self
-            .aliens
-            .iter()
-            .all(|alien| alien.state == AlienState::Dead)
-        {
-            self.increment_level_wave(ctx);
+    fn draw_name_entry(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let window_dimension = graphics::size(ctx);
+        self.text
+            .name_prompt
+            .draw_horizontal_center(0.3 * window_dimension.1, ctx);
+        self.text.name.draw_center(ctx);
+    }
+
+    fn draw_high_scores(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let mut title_pos = self.text.high_scores_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.high_scores_title.draw(title_pos, ctx);
+
+        let positions = menu_positions(&self.text.high_scores, ctx);
+        for (i, line) in self.text.high_scores.iter().enumerate() {
+            // the header line saying whose table this is
+            let color = if i == 0 { GRAY } else { WHITE };
+            line.draw_color(positions[i], color, ctx);
         }
     }
-    fn draw_difficulty_select(&mut self, ctx: &mut Context) {
+
+    fn high_scores_hint_pos(&self, ctx: &mut Context) -> na::Point2<f32> {
+        let mut pos = self.text.high_scores_hint.center(ctx);
+        pos[1] = 0.9 * graphics::size(ctx).1;
+        pos
+    }
+
+    fn draw_settings(&mut self, ctx: &mut Context) {
         self.background.draw_no_stars(ctx, &self.assets);
         let mut title_pos = self.text.math_title.center(ctx);
         title_pos[1] *= 0.5;
         self.text.math_title.draw(title_pos, ctx);
 
-        let window_dimension = graphics::size(ctx);
-        let mut y = 0.4 * window_dimension.1 as f32;
+        let positions = menu_positions(&self.text.settings_names, ctx);
+        for (i, settings_name) in self.text.settings_names.iter().enumerate() {
+            if i == self.settings_selection {
+                settings_name.draw(positions[i], ctx);
+            } else {
+                settings_name.draw_color(positions[i], GRAY, ctx);
+            }
+        }
+    }
 
-        for (i, difficulty_name) in self.text.difficulty_names.iter().enumerate() {
-            let vertical_size = difficulty_name.dest_pixel_dimensions(window_dimension).1;
-            let mut center = difficulty_name.center(ctx);
-            center[1] = y;
-            if i == self.difficulty_selection {
-                difficulty_name.draw(center, ctx);
+    fn draw_key_bindings(&mut self, ctx: &mut Context) {
+        self.background.draw_no_stars(ctx, &self.assets);
+        let mut title_pos = self.text.math_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.math_title.draw(title_pos, ctx);
+
+        let positions = menu_positions(&self.text.binding_names, ctx);
+        for (i, binding_name) in self.text.binding_names.iter().enumerate() {
+            if i == self.binding_selection {
+                binding_name.draw(positions[i], ctx);
             } else {
-                difficulty_name.draw_color(center, GRAY, ctx);
+                binding_name.draw_color(positions[i], GRAY, ctx);
             }
-            y += vertical_size * 1.075;
         }
     }
 
@@ -646,21 +2926,164 @@ impl MainState {
         self.text.math_title.draw(title_pos, ctx);
 
         let window_dimension = graphics::size(ctx);
-        let mut y = 0.4 * window_dimension.1 as f32;
+        let radius = 0.012 * window_dimension.0;
+        let positions = menu_positions(&self.text.level_names, ctx);
         for (i, level_name) in self.text.level_names.iter().enumerate() {
-            let vertical_size = level_name.dest_pixel_dimensions(window_dimension).1;
-            let mut center = level_name.center(ctx);
-            center[1] = y;
+            let level = &self.levels[i];
             if i == self.level_selection {
-                level_name.draw(center, ctx);
-            } else if self.levels[i].unlocked[self.difficulty_selection] {
-                level_name.draw_color(center, GRAY, ctx);
+                level_name.draw(positions[i], ctx);
+            } else if self.level_open(i) {
+                level_name.draw_color(positions[i], GRAY, ctx);
             } else {
-                level_name.draw_color(center, DARK_GRAY, ctx);
+                level_name.draw_color(positions[i], DARK_GRAY, ctx);
+            }
+            // best stars so far go to the right of the name
+            if level.unlocked[self.difficulty_selection] {
+                let (w, h) = level_name.dest_pixel_dimensions(window_dimension);
+                let left = na::Point2::new(positions[i][0] + w + radius, positions[i][1] + h / 2.0);
+                draw_stars(level.stars[self.difficulty_selection], left, radius, ctx);
             }
-            y += vertical_size * 1.075;
         }
+        let hint_pos = self.high_scores_hint_pos(ctx);
+        self.text.high_scores_hint.draw(hint_pos, ctx);
+    }
+    fn draw_results(&mut self, ctx: &mut Context) {
+        self.background.draw(ctx, &self.assets);
+        let mut title_pos = self.text.results_title.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.results_title.draw(title_pos, ctx);
+
+        let window_dimension = graphics::size(ctx);
+        let positions = menu_positions(&self.text.results, ctx);
+        for (line, pos) in self.text.results.iter().zip(&positions) {
+            line.draw(*pos, ctx);
+        }
+        // the stars go under the last line, centered
+        if let (Mode::Campaign, false, Some(last), Some(last_pos)) = (
+            self.mode,
+            self.zen(),
+            self.text.results.last(),
+            positions.last(),
+        ) {
+            let radius = 0.025 * window_dimension.0;
+            let width = radius * 2.2 * MAX_STARS as f32;
+            let last_height = last.dest_pixel_dimensions(window_dimension).1;
+            let y = last_pos[1] + last_height * 1.075 + radius * 1.5;
+            let left = na::Point2::new((window_dimension.0 - width) / 2.0, y);
+            draw_stars(self.score.stars(self.lives), left, radius, ctx);
+        }
+        self.text
+            .press_enter
+            .draw_horizontal_center(window_dimension.1 * 0.85, ctx);
+    }
+
+    // the score sits to the left of the spare guns
+    // right aligned to `right` of the way across the screen
+    fn draw_score(&self, score: &Score, right: f32, ctx: &mut Context) {
+        let screen = graphics::size(ctx);
+        let multiplier = score.multiplier();
+        let mut score = score.points.to_string();
+        if multiplier > 1 {
+            score += &format!("  x{}", multiplier);
+        }
+        let text = graphics::Text::new((score, self.assets.number_font, 32.0));
+        let scale = screen.0 / 1920.0;
+        let (tw, th) = text.dimensions(ctx);
+        let dest = na::Point2::new(
+            right * screen.0 - tw as f32 * scale,
+            0.925 * screen.1 - th as f32 * scale / 2.0,
+        );
+        let _ = graphics::draw(
+            ctx,
+            &text,
+            graphics::DrawParam::new()
+                .color(WHITE)
+                .dest(dest)
+                .scale(na::Vector2::new(scale, scale)),
+        );
+    }
+
+    // everyone in the lan match, one line each starting `left` and `top` of the
+    // way across and down the screen
+    fn draw_leaderboard(&self, left: f32, top: f32, size: f32, ctx: &mut Context) {
+        let lan = match &self.lan {
+            Some(lan) => lan,
+            None => return,
+        };
+        let screen = graphics::size(ctx);
+        let scale = screen.0 / 1920.0;
+        let mut y = top * screen.1;
+        for (i, entry) in lan.leaderboard.ranked().iter().enumerate() {
+            // before the first match there's only who's here to show
+            let line = match lan.seed {
+                Some(_) => format!(
+                    "{}. {}  {}  Wave {}{}",
+                    i + 1,
+                    entry.name,
+                    entry.score,
+                    entry.wave,
+                    if entry.finished { "  Done" } else { "" }
+                ),
+                None => entry.name.clone(),
+            };
+            let color = if entry.name == lan.name { WHITE } else { GRAY };
+            let text = graphics::Text::new((line, self.assets.main_font, size));
+            let (_, th) = text.dimensions(ctx);
+            let _ = graphics::draw(
+                ctx,
+                &text,
+                graphics::DrawParam::new()
+                    .color(color)
+                    .dest(na::Point2::new(left * screen.0, y))
+                    .scale(na::Vector2::new(scale, scale)),
+            );
+            y += th as f32 * scale * 1.1;
+        }
+    }
+
+    // whose turn it is in the hot seat, across the top of the screen
+    fn draw_turn(&self, ctx: &mut Context) {
+        let screen = graphics::size(ctx);
+        let text = graphics::Text::new((
+            self.coop.current().to_string() + "'s Turn",
+            self.assets.main_font,
+            40.0,
+        ));
+        let scale = screen.0 / 1920.0;
+        let (tw, _) = text.dimensions(ctx);
+        let dest = na::Point2::new(0.5 * screen.0 - tw as f32 * scale / 2.0, 0.02 * screen.1);
+        let _ = graphics::draw(
+            ctx,
+            &text,
+            graphics::DrawParam::new()
+                .color(GRAY)
+                .dest(dest)
+                .scale(na::Vector2::new(scale, scale)),
+        );
+    }
+
+    fn draw_clock(&self, ctx: &mut Context) {
+        let screen = graphics::size(ctx);
+        let text = graphics::Text::new((self.sprint.clock(), self.assets.number_font, 48.0));
+        let scale = screen.0 / 1920.0;
+        let (_, th) = text.dimensions(ctx);
+        let dest = na::Point2::new(0.07 * screen.0, 0.925 * screen.1 - th as f32 * scale / 2.0);
+        // the last ten seconds turn blue
+        let color = if self.sprint.remaining < 10000.0 {
+            BLUE
+        } else {
+            WHITE
+        };
+        let _ = graphics::draw(
+            ctx,
+            &text,
+            graphics::DrawParam::new()
+                .color(color)
+                .dest(dest)
+                .scale(na::Vector2::new(scale, scale)),
+        );
     }
+
     fn draw_won(&mut self, ctx: &mut Context) {
         self.background.draw(ctx, &self.assets);
         let mut title_pos = self.text.won_text.center(ctx);
@@ -716,61 +3139,137 @@ impl MainState {
                 let crosshair_pos =
                     to_screen_pos((alien.pos[0], alien.pos[1]), graphics::size(ctx));
                 self.crosshair.draw(crosshair_pos, ctx, &self.assets);
-                //draw the laser if the turret is firing
-                match self.turret.state {
-                    TurretState::Firing => {
-                        let screen_size = graphics::size(ctx);
-                        let turret_pos = self.turret.pixel_pos(screen_size);
-
-                        //make the lasers come out of the actual gunscar
-                        let left_pos = na::Point2::new(turret_pos[0] - 0.01*screen_size.0,turret_pos[1] - 0.01*screen_size.1);
-                        let right_pos = na::Point2::new(turret_pos[0] + 0.01*screen_size.0,turret_pos[1] - 0.01*screen_size.1);
-                        
-                        //left laser
-                        let laser = graphics::Mesh::new_line(
-                            ctx,
-                            &[
-                                left_pos,
-                                alien.pixel_pos(screen_size),
-                            ],
-                            4.0,
-                            graphics::Color::from((255, 0, 0, 255)),
-                        )
-                        .unwrap();
-                        let _ = graphics::draw(ctx, &laser, graphics::DrawParam::default());
-                        //right laser
-                        let laser = graphics::Mesh::new_line(
-                            ctx,
-                            &[
-                                right_pos,
-                                alien.pixel_pos(screen_size),
-                            ],
-                            4.0,
-                            graphics::Color::from((255, 0, 0, 255)),
-                        )
-                        .unwrap();
-                        let _ = graphics::draw(ctx, &laser, graphics::DrawParam::default());
-                    }
-                    TurretState::Resting => (),
-                }
+                draw_lasers(&self.turret, alien, ctx);
             }
             None => (),
         };
 
         //draw the aliens, turrets, and messages
         for alien in &mut self.aliens {
-            alien.draw(ctx, &mut self.assets);
+            alien.draw(ctx, &mut self.assets, self.settings.text_scale);
         }
         self.turret.draw(ctx, &mut self.assets);
-        self.turret.draw_lives(self.lives, ctx, &mut self.assets);
+        // a sprint has a clock instead of lives, and zen practice has neither
+        if self.mode == Mode::MadMinute {
+            self.draw_clock(ctx);
+        } else if !self.zen() {
+            self.turret.draw_lives(self.lives, ctx, &mut self.assets);
+        }
+        self.draw_score(&self.score, 0.93, ctx);
+        if self.mode == Mode::CoOp {
+            self.draw_turn(ctx);
+        }
+        if self.mode == Mode::Lan {
+            self.draw_leaderboard(0.78, 0.03, 28.0, ctx);
+        }
+
+        // word problems show their sentence for whichever alien is targeted
+        if let Some(target) = self.target {
+            let alien = &mut self.aliens[target];
+            if alien.state == AlienState::Alive {
+                let alien_pos = alien.pixel_pos(graphics::size(ctx));
+                if let Some(banner) = &mut alien.banner {
+                    banner.draw(alien_pos, self.settings.text_scale, ctx);
+                }
+                if self.settings.input_mode == InputMode::MultipleChoice {
+                    draw_choices(&alien.choices, alien.choices_pos(), ctx);
+                }
+            }
+        }
+        if self.settings.input_mode == InputMode::Typed {
+            self.keypad.draw(ctx);
+        }
+        self.picker.draw(ctx);
+        if !self.messages.is_empty() {
+            self.messages[0].draw(ctx);
+        }
+    }
+    fn draw_versus(&mut self, ctx: &mut Context) {
+        self.background.draw(ctx, &self.assets);
+        let screen = graphics::size(ctx);
+        let scale = screen.0 / 1920.0;
+        if let Ok(divider) = graphics::Mesh::new_line(
+            ctx,
+            &[
+                na::Point2::new(screen.0 / 2.0, 0.0),
+                na::Point2::new(screen.0 / 2.0, screen.1),
+            ],
+            2.0,
+            GRAY,
+        ) {
+            let _ = graphics::draw(ctx, &divider, graphics::DrawParam::default());
+        }
+        for (i, side) in self.sides.iter_mut().enumerate() {
+            if let Some(target) = side.target {
+                let alien = &side.aliens[target];
+                let crosshair_pos = to_screen_pos((alien.pos[0], alien.pos[1]), screen);
+                self.crosshair.draw(crosshair_pos, ctx, &self.assets);
+                draw_lasers(&side.turret, alien, ctx);
+            }
+            for alien in &mut side.aliens {
+                alien.draw(ctx, &mut self.assets, self.settings.text_scale);
+            }
+            side.turret.draw(ctx, &mut self.assets);
+            side.turret
+                .draw_lives_at(side.lives, side_x(i, 0.86), ctx, &mut self.assets);
+
+            let name = graphics::Text::new((PLAYER_NAMES[i], self.assets.main_font, 40.0));
+            let (tw, _) = name.dimensions(ctx);
+            let dest = na::Point2::new(
+                side_x(i, 0.5) * screen.0 - tw as f32 * scale / 2.0,
+                0.02 * screen.1,
+            );
+            let _ = graphics::draw(
+                ctx,
+                &name,
+                graphics::DrawParam::new()
+                    .color(GRAY)
+                    .dest(dest)
+                    .scale(na::Vector2::new(scale, scale)),
+            );
+        }
+        for (i, side) in self.sides.iter().enumerate() {
+            self.draw_score(&side.score, side_x(i, 0.84), ctx);
+        }
         if !self.messages.is_empty() {
             self.messages[0].draw(ctx);
         }
     }
+
+    // the frozen game stays in the background, dimmed behind the menu
+    fn draw_paused(&mut self, ctx: &mut Context) {
+        if self.mode == Mode::Versus {
+            self.draw_versus(ctx);
+        } else {
+            self.draw_playing(ctx);
+        }
+        let (w, h) = graphics::size(ctx);
+        if let Ok(shade) = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        ) {
+            let _ = graphics::draw(ctx, &shade, graphics::DrawParam::default());
+        }
+        let mut title_pos = self.text.paused.center(ctx);
+        title_pos[1] *= 0.5;
+        self.text.paused.draw(title_pos, ctx);
+
+        let positions = menu_positions(&self.text.pause_names, ctx);
+        for (i, pause_name) in self.text.pause_names.iter().enumerate() {
+            if i == self.pause_selection {
+                pause_name.draw(positions[i], ctx);
+            } else {
+                pause_name.draw_color(positions[i], GRAY, ctx);
+            }
+        }
+    }
+
     fn draw_dying(&mut self, ctx: &mut Context) {
         self.background.draw(ctx, &self.assets);
         for alien in &mut self.aliens {
-            alien.draw(ctx, &mut self.assets);
+            alien.draw(ctx, &mut self.assets, self.settings.text_scale);
         }
         self.turret.draw(ctx, &mut self.assets);
         self.turret.draw_lives(self.lives, ctx, &mut self.assets);
@@ -787,20 +3286,44 @@ impl event::EventHandler for MainState {
         self.dt = timer::delta(ctx);
         match &self.state {
             GameState::DifficultySelect => self.update_difficulty_select(ctx),
+            GameState::ModeSelect => self.update_mode_select(ctx),
+            GameState::SprintSetup => self.update_sprint_setup(ctx),
+            GameState::DailyCalendar => self.update_daily_calendar(ctx),
+            GameState::CoOpSetup => self.update_coop_setup(ctx),
+            GameState::LanMenu => self.update_lan_menu(ctx),
+            GameState::LanLobby => self.update_lan_lobby(ctx),
+            GameState::Settings => self.update_settings(ctx),
+            GameState::KeyBindings => self.update_key_bindings(ctx),
+            GameState::ProfileSelect => self.update_profile_select(ctx),
+            GameState::NameEntry(name_for) => {
+                let name_for = *name_for;
+                self.update_name_entry(ctx, name_for);
+            }
+            GameState::HighScores => self.update_high_scores(ctx),
             GameState::LevelSelect => self.update_level_select(ctx),
             GameState::LevelTransition(elapsed) => {
                 let x = *elapsed;
                 self.update_level_transition(ctx, x);
             }
             GameState::Playing => self.update_playing(ctx),
+            GameState::Sprint => self.update_sprint(ctx),
+            GameState::Versus => self.update_versus(ctx),
+            GameState::Paused => self.update_paused(ctx),
             GameState::Dying => self.update_dying(ctx),
             GameState::Dead => self.update_dead(ctx),
             GameState::Won => self.update_won(ctx),
+            GameState::Results => self.update_results(ctx),
             GameState::LevelComplete => self.update_level_complete(ctx),
         }
+        self.update_lan(ctx);
+        self.publish_session();
+        // quiet the music under messages while they're on screen
+        let ducked = self.state.answering() && !self.messages.is_empty();
+        self.assets.audio.set_ducked(ducked);
+        self.assets.audio.update(ctx, self.dt);
         //clear out the up key event, now that the update funcs have had a chance to see it
-        match self.up_key {
-            Some(_) => self.up_key = None,
+        match self.action {
+            Some(_) => self.action = None,
             _ => (),
         }
         Ok(())
@@ -810,15 +3333,29 @@ impl event::EventHandler for MainState {
         graphics::clear(ctx, [0.0, 0.0, 0.0, 1.0].into());
         match &mut self.state {
             GameState::DifficultySelect => self.draw_difficulty_select(ctx),
+            GameState::ModeSelect => self.draw_mode_select(ctx),
+            GameState::SprintSetup => self.draw_sprint_setup(ctx),
+            GameState::DailyCalendar => self.draw_daily_calendar(ctx),
+            GameState::CoOpSetup => self.draw_coop_setup(ctx),
+            GameState::LanMenu => self.draw_lan_menu(ctx),
+            GameState::LanLobby => self.draw_lan_lobby(ctx),
+            GameState::Settings => self.draw_settings(ctx),
+            GameState::KeyBindings => self.draw_key_bindings(ctx),
+            GameState::ProfileSelect => self.draw_profile_select(ctx),
+            GameState::NameEntry(_) => self.draw_name_entry(ctx),
+            GameState::HighScores => self.draw_high_scores(ctx),
             GameState::LevelSelect => self.draw_level_select(ctx),
             GameState::LevelTransition(elapsed) => {
                 let x = *elapsed;
                 self.draw_level_transition(ctx, x);
             }
-            GameState::Playing => self.draw_playing(ctx),
+            GameState::Playing | GameState::Sprint => self.draw_playing(ctx),
+            GameState::Versus => self.draw_versus(ctx),
+            GameState::Paused => self.draw_paused(ctx),
             GameState::Dying => self.draw_dying(ctx),
             GameState::Dead => self.draw_dead(ctx),
             GameState::Won => self.draw_won(ctx),
+            GameState::Results => self.draw_results(ctx),
             GameState::LevelComplete => self.draw_level_complete(ctx),
         }
         graphics::present(ctx)?;
@@ -831,17 +3368,161 @@ impl event::EventHandler for MainState {
     }
 
     fn text_input_event(&mut self, ctx: &mut Context, ch: char) {
-        if self.state == GameState::Playing {
-            if ('0' <= ch && ch <= '9') || ch == '-' {
-                self.turret.raw_text += &ch.to_string();
-                self.turret.text = MBText::new(
-                    self.turret.raw_text.clone(),
-                    &self.assets.number_font,
-                    WHITE,
-                    24.0,
-                    ctx,
-                );
+        match self.state {
+            GameState::Playing | GameState::Sprint => self.input_char(ctx, ch),
+            GameState::NameEntry(name_for)
+                if name_for.accepts(ch)
+                    && self.name_entry.chars().count() < name_for.max_length() =>
+            {
+                self.name_entry.push(ch);
+                self.refresh_name(ctx);
+            }
+            _ => (),
+        }
+    }
+
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, _button: MouseButton, x: f32, y: f32) {
+        let pos = na::Point2::new(x, y);
+        match self.state {
+            GameState::DifficultySelect => {
+                if let Some(i) = menu_item_at(&self.text.difficulty_names, pos, ctx) {
+                    self.difficulty_selection = i;
+                    self.action = Some(Action::Confirm);
+                } else {
+                    let corner = self.input_mode_pos(ctx);
+                    let (w, h) = self
+                        .input_mode_name()
+                        .dest_pixel_dimensions(graphics::size(ctx));
+                    if graphics::Rect::new(corner[0], corner[1], w, h).contains(pos) {
+                        self.action = Some(Action::TargetNext);
+                    }
+                    let corner = self.settings_pos(ctx);
+                    let (w, h) = self
+                        .text
+                        .settings
+                        .dest_pixel_dimensions(graphics::size(ctx));
+                    if graphics::Rect::new(corner[0], corner[1], w, h).contains(pos) {
+                        self.difficulty_selection = DIFFICULTY_NAMES.len();
+                        self.action = Some(Action::Confirm);
+                    }
+                    let corner = self.player_pos(ctx);
+                    let (w, h) = self.text.player.dest_pixel_dimensions(graphics::size(ctx));
+                    if graphics::Rect::new(corner[0], corner[1], w, h).contains(pos) {
+                        self.difficulty_selection = DIFFICULTY_NAMES.len() + 1;
+                        self.action = Some(Action::Confirm);
+                    }
+                }
+            }
+            GameState::ModeSelect => {
+                if let Some(i) = menu_item_at(&self.text.mode_names, pos, ctx) {
+                    self.mode_selection = i;
+                    self.action = Some(Action::Confirm);
+                }
+            }
+            GameState::ProfileSelect => {
+                if let Some(i) = menu_item_at(&self.text.profile_names, pos, ctx) {
+                    self.profile_selection = i;
+                    self.action = Some(Action::Confirm);
+                }
+            }
+            GameState::HighScores => {
+                // the header flips to the next player, anywhere else goes back
+                match menu_item_at(&self.text.high_scores, pos, ctx) {
+                    Some(0) => self.action = Some(Action::TargetNext),
+                    _ => self.action = Some(Action::Confirm),
+                }
+            }
+            GameState::Settings => {
+                if let Some(i) = menu_item_at(&self.text.settings_names, pos, ctx) {
+                    self.settings_selection = i;
+                    // the left half of a line steps it back, the right half
+                    // forward, so a volume can go down as well as up
+                    let corner = menu_positions(&self.text.settings_names, ctx)[i];
+                    let (w, _) =
+                        self.text.settings_names[i].dest_pixel_dimensions(graphics::size(ctx));
+                    self.action = Some(if SETTINGS_ITEMS[i] == SettingsItem::Controls {
+                        Action::Confirm
+                    } else if x < corner[0] + w / 2.0 {
+                        Action::TargetPrev
+                    } else {
+                        Action::TargetNext
+                    });
+                }
+            }
+            GameState::KeyBindings => {
+                if let Some(i) = menu_item_at(&self.text.binding_names, pos, ctx) {
+                    if !self.rebinding {
+                        self.binding_selection = i;
+                        self.action = Some(Action::Confirm);
+                    }
+                }
+            }
+            GameState::LevelSelect => {
+                if let Some(i) = menu_item_at(&self.text.level_names, pos, ctx) {
+                    if self.level_open(i) {
+                        self.level_selection = i;
+                        self.action = Some(Action::Confirm);
+                    }
+                }
+                let corner = self.high_scores_hint_pos(ctx);
+                let (w, h) = self
+                    .text
+                    .high_scores_hint
+                    .dest_pixel_dimensions(graphics::size(ctx));
+                if graphics::Rect::new(corner[0], corner[1], w, h).contains(pos) {
+                    self.action = Some(Action::TargetNext);
+                }
+            }
+            GameState::Playing | GameState::Sprint => self.click_playing(ctx, pos),
+            GameState::Versus => self.click_versus(ctx, pos),
+            GameState::SprintSetup => {
+                if let Some(i) = menu_item_at(&self.text.sprint_names, pos, ctx) {
+                    self.sprint_selection = i;
+                    self.action = Some(Action::Confirm);
+                }
+            }
+            GameState::LanMenu => {
+                if let Some(i) = menu_item_at(&self.text.lan_names, pos, ctx) {
+                    self.lan_selection = i;
+                    self.action = Some(Action::Confirm);
+                }
+            }
+            GameState::LanLobby => self.action = Some(Action::Confirm),
+            GameState::CoOpSetup => {
+                if let Some(i) = menu_item_at(&self.text.coop_names, pos, ctx) {
+                    self.coop_selection = i;
+                    self.action = Some(Action::Confirm);
+                }
+            }
+            // the sides of the month title page through months, anywhere else plays
+            GameState::DailyCalendar => {
+                let screen = graphics::size(ctx);
+                self.action = Some(if y < 0.2 * screen.1 && x < 0.5 * screen.0 {
+                    Action::TargetPrev
+                } else if y < 0.2 * screen.1 {
+                    Action::TargetNext
+                } else {
+                    Action::Confirm
+                });
+            }
+            GameState::Paused => {
+                if let Some(i) = menu_item_at(&self.text.pause_names, pos, ctx) {
+                    self.pause_selection = i;
+                    self.action = Some(Action::Confirm);
+                }
+            }
+            // anywhere counts as pressing enter on the "Press Enter" screens
+            GameState::Results | GameState::LevelComplete | GameState::Dead | GameState::Won => {
+                self.action = Some(Action::Confirm)
             }
+            _ => (),
+        }
+    }
+
+    // alt-tabbing away mid wave shouldn't cost a life
+    fn focus_event(&mut self, _ctx: &mut Context, gained: bool) {
+        if !gained && (self.state.answering() || self.state == GameState::Versus) {
+            self.pause();
         }
     }
 
@@ -856,13 +3537,101 @@ impl event::EventHandler for MainState {
     }
 
     fn key_up_event(&mut self, ctx: &mut Context, keycode: KeyCode, _keymod: KeyMods) {
-        match keycode {
-            KeyCode::Escape => match self.state {
-                GameState::LevelSelect => self.state = GameState::DifficultySelect,
-                GameState::DifficultySelect => event::quit(ctx),
-                _ => self.state = GameState::LevelSelect,
-            },
-            _ => self.up_key = Some(keycode),
+        // the key pressed on the bindings screen gets bound, whatever it is
+        if self.rebinding {
+            self.rebinding = false;
+            let action = ACTIONS[self.binding_selection];
+            if self.settings.rebind(action, keycode) {
+                self.settings.save();
+            } else {
+                self.assets.audio.play(Sound::Fail);
+            }
+            self.refresh_binding_names(ctx);
+            return;
+        }
+        // both players share the keyboard, so keys go by which half they're on
+        if self.state == GameState::Versus {
+            if let Some((side, key)) = versus_key(keycode) {
+                self.press_versus_key(ctx, side, key);
+                return;
+            }
+        }
+        match self.settings.action_for(keycode) {
+            Some(Action::Pause) => self.escape(ctx),
+            action => self.action = action,
+        }
+    }
+
+    fn gamepad_button_up_event(&mut self, ctx: &mut Context, btn: Button, _id: GamepadId) {
+        if self.state.answering() {
+            self.picker.visible = true;
+            match btn {
+                Button::DPadUp => self.picker.move_selection(0.0, -1.0),
+                Button::DPadDown => self.picker.move_selection(0.0, 1.0),
+                Button::DPadLeft => self.picker.move_selection(-1.0, 0.0),
+                Button::DPadRight => self.picker.move_selection(1.0, 0.0),
+                Button::South => {
+                    if let Some(key) = self.picker.selected_key() {
+                        self.press_key(ctx, key);
+                    }
+                }
+                Button::West | Button::East => self.action = Some(Action::Back),
+                Button::North => self.action = Some(Action::Confirm),
+                Button::LeftTrigger | Button::LeftTrigger2 => {
+                    self.action = Some(Action::TargetPrev);
+                }
+                Button::RightTrigger | Button::RightTrigger2 => {
+                    self.action = Some(Action::TargetNext);
+                }
+                Button::Start => self.escape(ctx),
+                _ => (),
+            }
+        } else {
+            match btn {
+                Button::DPadUp => self.action = Some(Action::MenuUp),
+                Button::DPadDown => self.action = Some(Action::MenuDown),
+                Button::DPadLeft => self.action = Some(Action::TargetPrev),
+                Button::DPadRight => self.action = Some(Action::TargetNext),
+                Button::South | Button::Start => self.action = Some(Action::Confirm),
+                Button::East => self.escape(ctx),
+                _ => (),
+            }
+        }
+    }
+
+    fn gamepad_axis_event(&mut self, _ctx: &mut Context, axis: Axis, value: f32, _id: GamepadId) {
+        // the left stick works like the d-pad, moving once each time it's pushed over
+        let direction = if value > 0.5 {
+            1
+        } else if value < -0.5 {
+            -1
+        } else {
+            0
+        };
+        let last = match axis {
+            Axis::LeftStickX => &mut self.stick.0,
+            Axis::LeftStickY => &mut self.stick.1,
+            _ => return,
+        };
+        if *last == direction {
+            return;
+        }
+        *last = direction;
+        // stick y points up, screen y points down
+        let (dx, dy) = match axis {
+            Axis::LeftStickX => (direction as f32, 0.0),
+            _ => (0.0, -direction as f32),
+        };
+        if direction == 0 {
+            return;
+        }
+        if self.state.answering() {
+            self.picker.visible = true;
+            self.picker.move_selection(dx, dy);
+        } else if dy < 0.0 {
+            self.action = Some(Action::MenuUp);
+        } else if dy > 0.0 {
+            self.action = Some(Action::MenuDown);
         }
     }
 }
@@ -876,24 +3645,51 @@ pub fn main() -> GameResult {
         path::PathBuf::from("./resources")
     };
 
+    // a player with no window, so a lan match can be tried out on one machine
+    let args: Vec<String> = env::args().collect();
+    if args.len() >= 3 && args[1] == "--lan-bot" {
+        let name = args.get(3).map(|name| name.as_str()).unwrap_or("Bot");
+        if let Err(e) = run_bot(&args[2], name) {
+            println!("the lan bot couldn't join {}\n{}", args[2], e);
+        }
+        return Ok(());
+    }
+
+    // a page on this machine a teacher can watch from their own
+    let dashboard = match args.iter().position(|arg| arg == "--dashboard") {
+        Some(i) => {
+            let port = args
+                .get(i + 1)
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(DEFAULT_DASHBOARD_PORT);
+            match Dashboard::start(port) {
+                Ok(dashboard) => {
+                    println!("the dashboard is at http://localhost:{}/", port);
+                    Some(dashboard)
+                }
+                Err(e) => {
+                    println!("couldn't start the dashboard on port {}\n{}", port, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let settings = Settings::load_from_file();
     let cb = ggez::ContextBuilder::new("Math Defense", "Jack Mott")
         .add_resource_path(resource_dir)
         .window_setup(
             conf::WindowSetup::default()
                 .title("Math Defense")                
         )
-        .window_mode(
-            conf::WindowMode::default()
-                .dimensions(1920.0/1.5,1080.0/1.5)
-                .fullscreen_type(conf::FullscreenType::Windowed)
-                .resizable(true),
-        );
+        .window_mode(settings.window_mode());
 
     let (ctx, event_loop) = &mut cb.build()?;
-    let state = &mut MainState::new(ctx)?;
-    state.assets.music.set_repeat(true);
-    //state.assets.music.set_volume(0.07);
-    let _ = state.assets.music.play_detached();
+    let state = &mut MainState::new(ctx, settings)?;
+    state.dashboard = dashboard;
+    state.apply_volume();
+    state.assets.audio.play_music(ctx, DEFAULT_MUSIC_FILE, 0.0);
     state.dt = std::time::Duration::new(0, 0);
     event::run(ctx, event_loop, state)
 }
@@ -18,6 +18,17 @@ impl Message {
             elapsed: 0.0,
         }
     }
+
+    /// In the number font, for text with symbols like √ or ¢ that the main
+    /// font doesn't have
+    pub fn numbers(text: String, duration: f32, assets: &Assets, ctx: &mut Context) -> Message {
+        Message {
+            text: MBText::new(text, &assets.number_font, WHITE, 128.0, ctx),
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
     pub fn update(&mut self, dt: std::time::Duration) {
         self.elapsed += dt.as_millis() as f32;
     }
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// The ways to play, picked after the difficulty
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum Mode {
+    Campaign,
+    Endless,
+    MadMinute,
+    Daily,
+    Versus,
+    CoOp,
+    Lan,
+}
+
+pub const MODES: [Mode; 7] = [
+    Mode::Campaign,
+    Mode::Endless,
+    Mode::MadMinute,
+    Mode::Daily,
+    Mode::Versus,
+    Mode::CoOp,
+    Mode::Lan,
+];
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Campaign => "Campaign",
+            Mode::Endless => "Endless",
+            Mode::MadMinute => "Mad Minute",
+            Mode::Daily => "Daily Challenge",
+            Mode::Versus => "Versus",
+            Mode::CoOp => "Co-op",
+            Mode::Lan => "LAN Match",
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+
+use crate::mode::*;
+
+const PERSONAL_BESTS_FILE: &str = "personal_bests.json";
+
+/// The best run in a mode for one profile at one difficulty, e.g. waves
+/// survived in endless
+#[derive(Deserialize, Serialize)]
+pub struct PersonalBest {
+    pub mode: Mode,
+    pub profile: String,
+    pub difficulty: usize,
+    pub best: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PersonalBests {
+    pub bests: Vec<PersonalBest>,
+}
+
+impl PersonalBests {
+    pub fn load_from_file() -> PersonalBests {
+        //if any of this fails, start with no bests
+        fn load_helper() -> Result<PersonalBests, String> {
+            let mut file =
+                File::open(PERSONAL_BESTS_FILE).map_err(|e| format!("file not found\n {}", e))?;
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)
+                .map_err(|e| format!("file could not be read\n{}", e))?;
+            let bests: PersonalBests = serde_json::from_slice(&buffer[..])
+                .map_err(|e| format!("file not valid\n{}", e))?;
+            Ok(bests)
+        }
+
+        match load_helper() {
+            Ok(bests) => bests,
+            Err(msg) => {
+                println!(
+                    "Error loading personal bests file.\nStarting fresh\n{}",
+                    msg
+                );
+                PersonalBests { bests: Vec::new() }
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        fn save_helper(bests: &PersonalBests) -> Result<(), String> {
+            let serialized = serde_json::to_string_pretty(bests)
+                .map_err(|_| "couldn't serialize personal bests")?;
+            let mut file = File::create(PERSONAL_BESTS_FILE)
+                .map_err(|_| "couldn't create save file for personal bests")?;
+            file.write_all(serialized.as_bytes())
+                .map_err(|_| "couldn't write to save file")?;
+            Ok(())
+        }
+        if let Err(msg) = save_helper(self) {
+            println!("{}", msg);
+        }
+    }
+
+    pub fn best(&self, mode: Mode, profile: &str, difficulty: usize) -> Option<u32> {
+        self.bests
+            .iter()
+            .find(|best| {
+                best.mode == mode && best.profile == profile && best.difficulty == difficulty
+            })
+            .map(|best| best.best)
+    }
+
+    /// Keeps `value` if it beats the old best, returns whether it did
+    pub fn record(&mut self, mode: Mode, profile: &str, difficulty: usize, value: u32) -> bool {
+        match self.bests.iter_mut().find(|best| {
+            best.mode == mode && best.profile == profile && best.difficulty == difficulty
+        }) {
+            Some(best) if value > best.best => best.best = value,
+            Some(_) => return false,
+            None => self.bests.push(PersonalBest {
+                mode,
+                profile: profile.to_string(),
+                difficulty,
+                best: value,
+            }),
+        }
+        true
+    }
+}
@@ -0,0 +1,333 @@
+use rand::*;
+use std::fmt;
+
+use crate::level::*;
+
+const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+// percentages offered by percent-of problems, the harder ones only
+// show up at the higher difficulties
+const PERCENTS: [i32; 7] = [50, 100, 10, 25, 20, 75, 5];
+const PERCENTS_DIFFICULTY: [usize; 4] = [2, 3, 5, 7];
+
+// coins and bills in cents, more of them are used at higher difficulties
+const MONEY: [i32; 6] = [1, 5, 10, 25, 100, 500];
+const MONEY_DIFFICULTY: [usize; 4] = [4, 5, 6, 6];
+// clock problems stick to quarter hours for rookies
+const MINUTE_STEP_DIFFICULTY: [i32; 4] = [15, 5, 5, 1];
+
+/// What the player has to type to destroy an alien.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Answer {
+    Number(i32),
+    Symbol(char),
+    /// Typed as plain cents (`125`) or dollars (`$1.25`)
+    Cents(i32),
+    /// A time on a 12 hour clock, typed as `4:15`
+    Time(i32, i32),
+}
+
+/// A generated problem, `text` is what gets shown to the player.
+pub struct Problem {
+    pub text: String,
+    pub answer: Answer,
+    // the two numbers the operation was applied to, when there are two
+    pub operands: Option<(i32, i32)>,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Symbol(c) => write!(f, "{}", c),
+            Answer::Cents(cents) => write!(f, "{}", format_money(*cents)),
+            Answer::Time(hour, minute) => write!(f, "{}:{:02}", hour, minute),
+        }
+    }
+}
+
+impl Answer {
+    pub fn matches(&self, input: &str) -> bool {
+        match self {
+            Answer::Number(n) => input.parse::<i32>() == Ok(*n),
+            Answer::Symbol(c) => input.chars().eq(std::iter::once(*c)),
+            Answer::Cents(cents) => parse_cents(input) == Some(*cents),
+            Answer::Time(hour, minute) => match parse_time(input) {
+                Some((h, m)) => h % 12 == hour % 12 && m == *minute,
+                None => false,
+            },
+        }
+    }
+}
+
+fn parse_cents(input: &str) -> Option<i32> {
+    let dollars = input.trim_start_matches('$');
+    if dollars.len() == input.len() && !input.contains('.') {
+        return input.parse::<i32>().ok();
+    }
+    let mut parts = dollars.splitn(2, '.');
+    let whole = match parts.next() {
+        Some("") => 0,
+        Some(whole) => whole.parse::<i32>().ok()?,
+        None => 0,
+    };
+    let cents = match parts.next() {
+        // "$1.5" means a dollar fifty, not a dollar and five cents
+        Some(cents) if cents.len() == 1 => cents.parse::<i32>().ok()? * 10,
+        Some(cents) if cents.len() == 2 => cents.parse::<i32>().ok()?,
+        Some(_) => return None,
+        None => 0,
+    };
+    Some(whole * 100 + cents)
+}
+
+fn parse_time(input: &str) -> Option<(i32, i32)> {
+    let mut parts = input.splitn(2, ':');
+    let hour = parts.next()?.parse::<i32>().ok()?;
+    let minute = parts.next()?;
+    if minute.len() != 2 {
+        return None;
+    }
+    let minute = minute.parse::<i32>().ok()?;
+    if !(1..=12).contains(&hour) || minute > 59 {
+        return None;
+    }
+    Some((hour, minute))
+}
+
+fn format_money(cents: i32) -> String {
+    if cents < 100 {
+        cents.to_string() + "¢"
+    } else if cents % 100 == 0 {
+        format!("${}", cents / 100)
+    } else {
+        format!("${}.{:02}", cents / 100, cents % 100)
+    }
+}
+
+// minutes are counted from midnight and shown on a 12 hour clock
+fn clock_hour(minutes: i32) -> i32 {
+    match (minutes / 60) % 12 {
+        0 => 12,
+        hour => hour,
+    }
+}
+
+fn format_time(minutes: i32) -> String {
+    format!("{}:{:02}", clock_hour(minutes), minutes % 60)
+}
+
+fn superscript(n: i32) -> String {
+    n.to_string()
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(d) => SUPERSCRIPTS[d as usize],
+            None => c,
+        })
+        .collect()
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// gen_range panics on an empty range, so widen it when a level asks for one
+pub fn gen_between<R: Rng>(rng: &mut R, min: i32, max: i32) -> i32 {
+    if max <= min {
+        min
+    } else {
+        rng.gen_range(min, max)
+    }
+}
+
+fn round_to(n: i32, place: i32) -> i32 {
+    (n + place / 2).div_euclid(place) * place
+}
+
+/// Generates the label shown on an alien and the answer that destroys it.
+pub fn gen_problem<R: Rng>(
+    operation: Operation,
+    min_number: i32,
+    max_number: i32,
+    difficulty: usize,
+    rng: &mut R,
+) -> Problem {
+    let (text, answer, operands) = match operation {
+        Operation::MoneySum => {
+            let count = gen_between(rng, min_number.max(2), max_number.max(3)).min(5);
+            let coins: Vec<i32> = (0..count)
+                .map(|_| MONEY[rng.gen_range(0, MONEY_DIFFICULTY[difficulty])])
+                .collect();
+            let text = coins
+                .iter()
+                .map(|coin| format_money(*coin))
+                .collect::<Vec<String>>()
+                .join("+");
+            (text, Answer::Cents(coins.iter().sum()), None)
+        }
+        Operation::MakeChange => {
+            let paid = if difficulty < 2 { 100 } else { 500 };
+            let step = if difficulty == 0 { 25 } else { 5 };
+            let price = rng.gen_range(1, paid / step) * step;
+            (
+                format_money(paid) + operation.symbol() + &format_money(price),
+                Answer::Cents(paid - price),
+                Some((paid, price)),
+            )
+        }
+        Operation::TimeAfter | Operation::TimeElapsed => {
+            let step = MINUTE_STEP_DIFFICULTY[difficulty];
+            let start = rng.gen_range(60, 13 * 60) / step * step;
+            // up to an hour for rookies, a few hours for space marines
+            let longest = 60 * (1 + difficulty as i32);
+            let elapsed = rng.gen_range(1, longest / step + 1) * step;
+            let end = start + elapsed;
+            if operation == Operation::TimeAfter {
+                (
+                    format_time(start) + operation.symbol() + &elapsed.to_string() + " min",
+                    Answer::Time(clock_hour(end), end % 60),
+                    None,
+                )
+            } else {
+                (
+                    format_time(start) + operation.symbol() + &format_time(end),
+                    Answer::Number(elapsed),
+                    None,
+                )
+            }
+        }
+        Operation::Compare => {
+            let a = gen_between(rng, min_number, max_number);
+            // equal pairs would almost never come up by chance
+            let b = if rng.gen_range(0, 4) == 0 {
+                a
+            } else {
+                gen_between(rng, min_number, max_number)
+            };
+            let symbol = if a < b {
+                '<'
+            } else if a > b {
+                '>'
+            } else {
+                '='
+            };
+            (
+                a.to_string() + operation.symbol() + &b.to_string(),
+                Answer::Symbol(symbol),
+                None,
+            )
+        }
+        Operation::RoundTen | Operation::RoundHundred => {
+            let place = if operation == Operation::RoundTen {
+                10
+            } else {
+                100
+            };
+            let n = gen_between(rng, min_number, max_number);
+            (
+                n.to_string() + operation.symbol(),
+                Answer::Number(round_to(n, place)),
+                None,
+            )
+        }
+        Operation::Power => {
+            let base = gen_between(rng, min_number, max_number);
+            // keep cubes to small bases so the answers stay mental math
+            let exponent = if base.abs() <= 5 {
+                rng.gen_range(2, 4)
+            } else {
+                2
+            };
+            (
+                base.to_string() + &superscript(exponent),
+                Answer::Number(base.pow(exponent as u32)),
+                Some((base, exponent)),
+            )
+        }
+        Operation::SquareRoot => {
+            // pick the root first so we only ever show perfect squares
+            let root = gen_between(rng, min_number.max(0), max_number);
+            (
+                operation.symbol().to_string() + &(root * root).to_string(),
+                Answer::Number(root),
+                None,
+            )
+        }
+        Operation::Modulo => {
+            let b = gen_between(rng, min_number.max(2), max_number.max(3));
+            let a = gen_between(rng, min_number.max(0), max_number * 2);
+            (
+                a.to_string() + operation.symbol() + &b.to_string(),
+                Answer::Number(a % b),
+                Some((a, b)),
+            )
+        }
+        Operation::PercentOf => {
+            let percent = PERCENTS[rng.gen_range(0, PERCENTS_DIFFICULTY[difficulty])];
+            // the base has to be a multiple of this for the answer to be whole
+            let step = 100 / gcd(percent, 100);
+            let base = gen_between(rng, min_number.max(1), max_number.max(2)) * step;
+            (
+                percent.to_string() + operation.symbol() + &base.to_string(),
+                Answer::Number(percent * base / 100),
+                None,
+            )
+        }
+        _ => {
+            let (mut num1, mut num2) = if operation == Operation::Divide {
+                let mut a;
+                let mut b;
+                loop {
+                    a = rng.gen_range(min_number, max_number);
+                    b = if a == min_number {
+                        a
+                    } else {
+                        rng.gen_range(min_number, a)
+                    };
+                    if b == 0 {
+                        continue;
+                    }
+                    if a % b == 0 {
+                        break;
+                    }
+                }
+                (a, b)
+            } else {
+                (
+                    rng.gen_range(min_number, max_number),
+                    rng.gen_range(min_number, max_number),
+                )
+            };
+
+            // make subtraction never negative until 3rd difficulty level
+            if difficulty < 2 && operation == Operation::Subtract {
+                let t = num2;
+                if num2 > num1 {
+                    num2 = num1;
+                    num1 = t;
+                }
+            }
+
+            let answer = match operation {
+                Operation::Add => num1 + num2,
+                Operation::Subtract => num1 - num2,
+                Operation::Multiply => num1 * num2,
+                _ => num1 / num2,
+            };
+            (
+                num1.to_string() + operation.symbol() + &num2.to_string(),
+                Answer::Number(answer),
+                Some((num1, num2)),
+            )
+        }
+    };
+    Problem {
+        text,
+        answer,
+        operands,
+    }
+}
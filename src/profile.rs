@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+
+const PROFILES_FILE: &str = "profiles.json";
+pub const MAX_NAME_LENGTH: usize = 12;
+
+/// The players on this machine, scores and bests are kept per profile
+#[derive(Deserialize, Serialize)]
+pub struct Profiles {
+    pub names: Vec<String>,
+    pub current: usize,
+    /// Profiles that play in zen practice, where nothing can be lost
+    #[serde(default)]
+    pub zen: Vec<String>,
+}
+
+impl Profiles {
+    pub fn load_from_file() -> Profiles {
+        //if any of this fails, start with a single default profile
+        fn load_helper() -> Result<Profiles, String> {
+            let mut file =
+                File::open(PROFILES_FILE).map_err(|e| format!("file not found\n {}", e))?;
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)
+                .map_err(|e| format!("file could not be read\n{}", e))?;
+            let profiles: Profiles = serde_json::from_slice(&buffer[..])
+                .map_err(|e| format!("file not valid\n{}", e))?;
+            if profiles.current >= profiles.names.len() {
+                return Err("current profile doesn't exist".to_string());
+            }
+            Ok(profiles)
+        }
+
+        match load_helper() {
+            Ok(profiles) => profiles,
+            Err(msg) => {
+                println!("Error loading profiles file.\nUsing default\n{}", msg);
+                let profiles = Profiles::new();
+                profiles.save();
+                profiles
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        fn save_helper(profiles: &Profiles) -> Result<(), String> {
+            let serialized = serde_json::to_string_pretty(profiles)
+                .map_err(|_| "couldn't serialize profiles")?;
+            let mut file = File::create(PROFILES_FILE)
+                .map_err(|_| "couldn't create save file for profiles")?;
+            file.write_all(serialized.as_bytes())
+                .map_err(|_| "couldn't write to save file")?;
+            Ok(())
+        }
+        if let Err(msg) = save_helper(self) {
+            println!("{}", msg);
+        }
+    }
+
+    pub fn current_name(&self) -> &str {
+        &self.names[self.current]
+    }
+
+    pub fn zen(&self) -> bool {
+        self.zen.iter().any(|name| name == self.current_name())
+    }
+
+    pub fn toggle_zen(&mut self) {
+        let name = self.current_name().to_string();
+        match self.zen.iter().position(|other| *other == name) {
+            Some(i) => {
+                self.zen.remove(i);
+            }
+            None => self.zen.push(name),
+        }
+    }
+
+    /// Adds a profile and switches to it, or just switches if the name is taken
+    pub fn add(&mut self, name: String) {
+        match self.names.iter().position(|other| *other == name) {
+            Some(i) => self.current = i,
+            None => {
+                self.names.push(name);
+                self.current = self.names.len() - 1;
+            }
+        }
+    }
+
+    pub fn new() -> Profiles {
+        Profiles {
+            names: vec!["Player 1".to_string()],
+            current: 0,
+            zen: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::nalgebra as na;
+use ggez::Context;
+
+use crate::ggez_utility::*;
+use crate::level::*;
+
+// a harder setting is worth more per alien
+const DIFFICULTY_POINTS: [f32; 4] = [1.0, 1.5, 2.0, 3.0];
+// every few answers in a row bumps the multiplier, up to a cap
+const STREAK_STEP: u32 = 5;
+const MAX_MULTIPLIER: u32 = 4;
+// aliens bottom out at 0.9, hitting one near the top nearly doubles it
+const BOTTOM: f32 = 0.9;
+
+pub const MAX_STARS: u32 = 3;
+const GOLD: Color = Color {
+    r: 1.0,
+    g: 0.8,
+    b: 0.1,
+    a: 1.0,
+};
+
+fn operation_points(operation: Operation) -> f32 {
+    match operation {
+        Operation::Add | Operation::Subtract | Operation::Compare | Operation::RoundTen => 10.0,
+        Operation::Multiply
+        | Operation::Divide
+        | Operation::RoundHundred
+        | Operation::MoneySum
+        | Operation::TimeAfter => 15.0,
+        Operation::Power
+        | Operation::SquareRoot
+        | Operation::Modulo
+        | Operation::PercentOf
+        | Operation::MakeChange
+        | Operation::TimeElapsed => 20.0,
+        Operation::WordProblem => 25.0,
+    }
+}
+
+/// Points and answer stats for the level being played
+pub struct Score {
+    pub points: u32,
+    pub streak: u32,
+    pub best_streak: u32,
+    pub correct: u32,
+    pub wrong: u32,
+    /// Quickest correct answer after targeting an alien, in millis
+    pub fastest: Option<f32>,
+    pub lives_at_start: usize,
+    // the alien being timed and how long it's been targeted
+    timing: Option<usize>,
+    timer: f32,
+}
+
+impl Score {
+    pub fn new(lives: usize) -> Score {
+        Score {
+            points: 0,
+            streak: 0,
+            best_streak: 0,
+            correct: 0,
+            wrong: 0,
+            fastest: None,
+            lives_at_start: lives,
+            timing: None,
+            timer: 0.0,
+        }
+    }
+
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.streak / STREAK_STEP).min(MAX_MULTIPLIER)
+    }
+
+    /// Times how long the current target has been targeted
+    pub fn update(&mut self, dt: std::time::Duration, target: Option<usize>) {
+        if target != self.timing {
+            self.timing = target;
+            self.timer = 0.0;
+        } else {
+            self.timer += dt.as_millis() as f32;
+        }
+    }
+
+    /// Starts timing over, for when a new wave reuses alien indices
+    pub fn reset_timer(&mut self) {
+        self.timing = None;
+        self.timer = 0.0;
+    }
+
+    /// Scores a destroyed alien that was at `height` on screen
+    pub fn hit(&mut self, operation: Operation, difficulty: usize, height: f32) {
+        let height_bonus = 1.0 + (BOTTOM - height).clamp(0.0, BOTTOM);
+        let points = operation_points(operation) * DIFFICULTY_POINTS[difficulty] * height_bonus;
+        self.points += points as u32 * self.multiplier();
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        self.correct += 1;
+        self.fastest = Some(match self.fastest {
+            Some(fastest) => fastest.min(self.timer),
+            None => self.timer,
+        });
+    }
+
+    pub fn miss(&mut self) {
+        self.streak = 0;
+        self.wrong += 1;
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        let answers = self.correct + self.wrong;
+        if answers == 0 {
+            1.0
+        } else {
+            self.correct as f32 / answers as f32
+        }
+    }
+
+    /// Three stars for a clean run, fewer for misses and lost guns
+    pub fn stars(&self, lives: usize) -> u32 {
+        let lives_lost = self.lives_at_start.saturating_sub(lives);
+        let accuracy = self.accuracy();
+        if accuracy >= 0.9 && lives_lost == 0 {
+            3
+        } else if accuracy >= 0.7 && lives_lost <= 1 {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// The lines shown on the results screen, stars are drawn under them
+    pub fn summary(&self) -> Vec<String> {
+        let fastest = match self.fastest {
+            Some(fastest) => format!("{:.1}s", fastest / 1000.0),
+            None => "-".to_string(),
+        };
+        vec![
+            format!("Score: {}", self.points),
+            format!("Accuracy: {}%", (self.accuracy() * 100.0).round() as i32),
+            format!("Fastest Answer: {}", fastest),
+            format!("Best Streak: {}", self.best_streak),
+        ]
+    }
+}
+
+fn star_points(center: na::Point2<f32>, radius: f32) -> Vec<na::Point2<f32>> {
+    (0..10)
+        .map(|i| {
+            let r = if i % 2 == 0 { radius } else { radius * 0.45 };
+            let angle = std::f32::consts::PI * (i as f32 / 5.0 - 0.5);
+            na::Point2::new(center[0] + r * angle.cos(), center[1] + r * angle.sin())
+        })
+        .collect()
+}
+
+/// Draws `MAX_STARS` stars in a row starting at `left`, the first `stars`
+/// of them filled in
+pub fn draw_stars(stars: u32, left: na::Point2<f32>, radius: f32, ctx: &mut Context) {
+    for i in 0..MAX_STARS {
+        let center = na::Point2::new(left[0] + radius + i as f32 * radius * 2.2, left[1]);
+        let color = if i < stars { GOLD } else { DARK_GRAY };
+        if let Ok(star) = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::fill(),
+            &star_points(center, radius),
+            color,
+        ) {
+            let _ = graphics::draw(ctx, &star, DrawParam::default());
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use ggez::conf;
+use ggez::event::KeyCode;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+
+use crate::choice::*;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+pub const RESOLUTIONS: [(f32, f32); 4] = [
+    (1280.0, 720.0),
+    (1600.0, 900.0),
+    (1920.0, 1080.0),
+    (2560.0, 1440.0),
+];
+pub const TEXT_SCALES: [f32; 4] = [0.75, 1.0, 1.25, 1.5];
+
+/// What a key or button press means, whichever key it was
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq)]
+pub enum Action {
+    Confirm,
+    Back,
+    TargetPrev,
+    TargetNext,
+    MenuUp,
+    MenuDown,
+    Pause,
+}
+
+pub const ACTIONS: [Action; 7] = [
+    Action::Confirm,
+    Action::Back,
+    Action::TargetPrev,
+    Action::TargetNext,
+    Action::MenuUp,
+    Action::MenuDown,
+    Action::Pause,
+];
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Confirm => "Confirm",
+            Action::Back => "Delete",
+            Action::TargetPrev => "Previous Target",
+            Action::TargetNext => "Next Target",
+            Action::MenuUp => "Menu Up",
+            Action::MenuDown => "Menu Down",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+/// The lines on the settings screen, in order
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SettingsItem {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    VoiceVolume,
+    Mute,
+    Voice,
+    Fullscreen,
+    Resolution,
+    TextSize,
+    InputMode,
+    Controls,
+}
+
+pub const SETTINGS_ITEMS: [SettingsItem; 11] = [
+    SettingsItem::MasterVolume,
+    SettingsItem::MusicVolume,
+    SettingsItem::SfxVolume,
+    SettingsItem::VoiceVolume,
+    SettingsItem::Mute,
+    SettingsItem::Voice,
+    SettingsItem::Fullscreen,
+    SettingsItem::Resolution,
+    SettingsItem::TextSize,
+    SettingsItem::InputMode,
+    SettingsItem::Controls,
+];
+
+/// Keys are stored by name, e.g. "Return" or "NumpadEnter"
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Binding {
+    pub action: Action,
+    pub keys: Vec<String>,
+}
+
+// anything missing from an older settings file gets its default
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub bindings: Vec<Binding>,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub voice_volume: f32,
+    pub muted: bool,
+    /// Read the targeted problem aloud, for early readers
+    pub voice: bool,
+    pub fullscreen: bool,
+    pub resolution: (f32, f32),
+    /// Size of the problem text on aliens and word problem banners
+    pub text_scale: f32,
+    pub input_mode: InputMode,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings::new()
+    }
+}
+
+// steps through `options` from wherever `current` is, wrapping at the ends
+pub fn cycle<T: Copy + PartialEq>(options: &[T], current: T, direction: i32) -> T {
+    let len = options.len() as i32;
+    let index = options.iter().position(|o| *o == current).unwrap_or(0) as i32;
+    options[(index + direction).rem_euclid(len) as usize]
+}
+
+// volumes go up and down by 10%, stopping at silent and full so a step
+// past the end never jumps straight to the other one
+fn step_volume(volume: f32, direction: i32) -> f32 {
+    let tenths = (volume * 10.0).round() as i32 + direction;
+    tenths.clamp(0, 10) as f32 / 10.0
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+pub fn key_name(keycode: KeyCode) -> String {
+    format!("{:?}", keycode)
+}
+
+impl Settings {
+    pub fn load_from_file() -> Settings {
+        //if any of this fails, use the default settings
+        fn load_helper() -> Result<Settings, String> {
+            let mut file =
+                File::open(SETTINGS_FILE).map_err(|e| format!("file not found\n {}", e))?;
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)
+                .map_err(|e| format!("file could not be read\n{}", e))?;
+            let mut settings: Settings = serde_json::from_slice(&buffer[..])
+                .map_err(|e| format!("file not valid\n{}", e))?;
+            // actions added since the file was saved get their default keys
+            for binding in Settings::new().bindings {
+                if settings.bindings.iter().all(|b| b.action != binding.action) {
+                    settings.bindings.push(binding);
+                }
+            }
+            Ok(settings)
+        }
+
+        match load_helper() {
+            Ok(settings) => settings,
+            Err(msg) => {
+                println!("Error loading settings file.\nUsing default\n{}", msg);
+                let settings = Settings::new();
+                settings.save();
+                settings
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        fn save_helper(settings: &Settings) -> Result<(), String> {
+            let serialized = serde_json::to_string_pretty(settings)
+                .map_err(|_| "couldn't serialize settings")?;
+            let mut file = File::create(SETTINGS_FILE)
+                .map_err(|_| "couldn't create save file for settings")?;
+            file.write_all(serialized.as_bytes())
+                .map_err(|_| "couldn't write to save file")?;
+            Ok(())
+        }
+        if let Err(msg) = save_helper(self) {
+            println!("{}", msg);
+        }
+    }
+
+    pub fn music_level(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume * self.music_volume
+        }
+    }
+
+    pub fn sfx_level(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume * self.sfx_volume
+        }
+    }
+
+    pub fn voice_level(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume * self.voice_volume
+        }
+    }
+
+    pub fn window_mode(&self) -> conf::WindowMode {
+        let fullscreen_type = if self.fullscreen {
+            conf::FullscreenType::Desktop
+        } else {
+            conf::FullscreenType::Windowed
+        };
+        conf::WindowMode::default()
+            .dimensions(self.resolution.0, self.resolution.1)
+            .fullscreen_type(fullscreen_type)
+            .resizable(true)
+    }
+
+    /// The line shown for `item`, e.g. "Music Volume: 70%"
+    pub fn item_label(&self, item: SettingsItem) -> String {
+        let percent = |value: f32| format!("{}%", (value * 100.0).round() as i32);
+        match item {
+            SettingsItem::MasterVolume => "Volume: ".to_string() + &percent(self.master_volume),
+            SettingsItem::MusicVolume => "Music Volume: ".to_string() + &percent(self.music_volume),
+            SettingsItem::SfxVolume => "Effects Volume: ".to_string() + &percent(self.sfx_volume),
+            SettingsItem::VoiceVolume => "Voice Volume: ".to_string() + &percent(self.voice_volume),
+            SettingsItem::Mute => "Mute: ".to_string() + on_off(self.muted),
+            SettingsItem::Voice => "Read Problems Aloud: ".to_string() + on_off(self.voice),
+            SettingsItem::Fullscreen => "Fullscreen: ".to_string() + on_off(self.fullscreen),
+            SettingsItem::Resolution => {
+                format!("Resolution: {}x{}", self.resolution.0, self.resolution.1)
+            }
+            SettingsItem::TextSize => "Text Size: ".to_string() + &percent(self.text_scale),
+            SettingsItem::InputMode => "Answers: ".to_string() + self.input_mode.name(),
+            SettingsItem::Controls => "Controls".to_string(),
+        }
+    }
+
+    /// Moves `item` one step, `direction` is 1 or -1. Controls is a link to
+    /// the bindings screen so there's nothing to change.
+    pub fn adjust(&mut self, item: SettingsItem, direction: i32) {
+        match item {
+            SettingsItem::MasterVolume => {
+                self.master_volume = step_volume(self.master_volume, direction)
+            }
+            SettingsItem::MusicVolume => {
+                self.music_volume = step_volume(self.music_volume, direction)
+            }
+            SettingsItem::SfxVolume => self.sfx_volume = step_volume(self.sfx_volume, direction),
+            SettingsItem::VoiceVolume => {
+                self.voice_volume = step_volume(self.voice_volume, direction)
+            }
+            SettingsItem::Mute => self.muted = !self.muted,
+            SettingsItem::Voice => self.voice = !self.voice,
+            SettingsItem::Fullscreen => self.fullscreen = !self.fullscreen,
+            SettingsItem::Resolution => {
+                self.resolution = cycle(&RESOLUTIONS, self.resolution, direction)
+            }
+            SettingsItem::TextSize => {
+                self.text_scale = cycle(&TEXT_SCALES, self.text_scale, direction)
+            }
+            SettingsItem::InputMode => {
+                self.input_mode = cycle(&INPUT_MODES, self.input_mode, direction)
+            }
+            SettingsItem::Controls => (),
+        }
+    }
+
+    fn volume_mut(&mut self, item: SettingsItem) -> Option<&mut f32> {
+        match item {
+            SettingsItem::MasterVolume => Some(&mut self.master_volume),
+            SettingsItem::MusicVolume => Some(&mut self.music_volume),
+            SettingsItem::SfxVolume => Some(&mut self.sfx_volume),
+            SettingsItem::VoiceVolume => Some(&mut self.voice_volume),
+            _ => None,
+        }
+    }
+
+    /// What Confirm does to `item`, the same as stepping it forward except
+    /// a full volume goes back to silent, so there's a way down without
+    /// left and right
+    pub fn advance(&mut self, item: SettingsItem) {
+        if let Some(volume) = self.volume_mut(item) {
+            if *volume >= 1.0 {
+                *volume = 0.0;
+                return;
+            }
+        }
+        self.adjust(item, 1);
+    }
+
+    pub fn action_for(&self, keycode: KeyCode) -> Option<Action> {
+        let name = key_name(keycode);
+        self.bindings
+            .iter()
+            .find(|binding| binding.keys.contains(&name))
+            .map(|binding| binding.action)
+    }
+
+    /// Shown on the bindings screen, e.g. "Confirm: Return, NumpadEnter"
+    pub fn binding_label(&self, action: Action) -> String {
+        let keys = self
+            .bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .map(|binding| binding.keys.join(", "))
+            .unwrap_or_default();
+        format!("{}: {}", action.name(), keys)
+    }
+
+    /// Makes `keycode` the only key for `action`. Any other action that had
+    /// it swaps to `action`'s old keys, so nothing is left without a key.
+    /// Returns false, changing nothing, when there's nothing to swap.
+    pub fn rebind(&mut self, action: Action, keycode: KeyCode) -> bool {
+        let name = key_name(keycode);
+        let old_keys: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|b| b.action == action)
+            .flat_map(|b| b.keys.iter().filter(|key| **key != name).cloned())
+            .collect();
+        let mut bindings = self.bindings.clone();
+        for binding in bindings.iter_mut().filter(|b| b.action != action) {
+            if binding.keys.contains(&name) {
+                binding.keys.retain(|key| *key != name);
+                if binding.keys.is_empty() {
+                    binding.keys = old_keys.clone();
+                }
+                if binding.keys.is_empty() {
+                    return false;
+                }
+            }
+        }
+        match bindings.iter_mut().find(|b| b.action == action) {
+            Some(binding) => binding.keys = vec![name],
+            None => bindings.push(Binding {
+                action,
+                keys: vec![name],
+            }),
+        }
+        self.bindings = bindings;
+        true
+    }
+
+    pub fn new() -> Settings {
+        let binding = |action, keys: &[KeyCode]| Binding {
+            action,
+            keys: keys.iter().map(|key| key_name(*key)).collect(),
+        };
+        Settings {
+            bindings: vec![
+                binding(Action::Confirm, &[KeyCode::Return, KeyCode::NumpadEnter]),
+                binding(Action::Back, &[KeyCode::Back]),
+                binding(Action::TargetPrev, &[KeyCode::Left]),
+                binding(Action::TargetNext, &[KeyCode::Right]),
+                binding(Action::MenuUp, &[KeyCode::Up]),
+                binding(Action::MenuDown, &[KeyCode::Down]),
+                binding(Action::Pause, &[KeyCode::Escape]),
+            ],
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            voice_volume: 1.0,
+            muted: false,
+            voice: false,
+            fullscreen: false,
+            resolution: RESOLUTIONS[0],
+            text_scale: 1.0,
+            input_mode: InputMode::Typed,
+        }
+    }
+}
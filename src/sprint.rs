@@ -0,0 +1,132 @@
+use crate::level::*;
+use crate::score::*;
+use crate::settings::*;
+
+pub const SPRINT_OPERATIONS: [Operation; 4] = [
+    Operation::Add,
+    Operation::Subtract,
+    Operation::Multiply,
+    Operation::Divide,
+];
+// the numbers in each problem, like a worksheet of facts to 10
+pub const SPRINT_RANGES: [(i32, i32); 4] = [(0, 5), (0, 10), (0, 12), (0, 20)];
+pub const SPRINT_MINUTES: [u32; 5] = [1, 2, 3, 4, 5];
+const SPRINT_SPEED: f32 = 2.5;
+// aliens come in batches this big, the next one starts once they're all on screen
+const BATCH_SIZE: usize = 5;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SprintItem {
+    Minutes,
+    Operation,
+    Range,
+    Start,
+}
+
+pub const SPRINT_ITEMS: [SprintItem; 4] = [
+    SprintItem::Minutes,
+    SprintItem::Operation,
+    SprintItem::Range,
+    SprintItem::Start,
+];
+
+/// A timed drill of one operation, answering as many as possible before the
+/// clock runs out
+pub struct Sprint {
+    pub minutes: u32,
+    pub operation: Operation,
+    pub range: (i32, i32),
+    /// Millis left on the clock
+    pub remaining: f32,
+    /// Aliens that reached the bottom without being answered
+    pub missed: u32,
+    /// Holds the one wave each batch of aliens is made from
+    pub level: Level,
+}
+
+impl Sprint {
+    pub fn new() -> Sprint {
+        Sprint {
+            minutes: 1,
+            operation: Operation::Add,
+            range: (0, 10),
+            remaining: 0.0,
+            missed: 0,
+            level: Level {
+                waves: Vec::new(),
+                background_file: "/spacebg3.jpg".to_string(),
+                title: "Mad Minute".to_string(),
+                unlocked: [true; 4],
+                music_file: None,
+                stars: [0; 4],
+                unlock_rules: Vec::new(),
+            },
+        }
+    }
+
+    /// Winds the clock back up and builds the wave for the chosen drill
+    pub fn start(&mut self, difficulty: usize) {
+        let (min_number, max_number) = self.range;
+        self.remaining = self.minutes as f32 * 60000.0;
+        self.missed = 0;
+        // the numbers are exactly what was picked, difficulty only speeds it
+        // up. problems draw up to but not including the max, so add one to
+        // get every fact up to it
+        self.level.waves = vec![Wave {
+            groups: vec![WaveGroup {
+                operation: self.operation,
+                speed: SPRINT_SPEED * SPEED_DIFFICULTY[difficulty],
+                num_ships: BATCH_SIZE,
+                min_number,
+                max_number: max_number + 1,
+                template_file: None,
+                kind: AlienKind::Normal,
+                pattern: Pattern::Straight,
+            }],
+        }];
+    }
+
+    pub fn item_label(&self, item: SprintItem) -> String {
+        match item {
+            SprintItem::Minutes if self.minutes == 1 => "Time: < 1 Minute >".to_string(),
+            SprintItem::Minutes => format!("Time: < {} Minutes >", self.minutes),
+            SprintItem::Operation => format!("Operation: < {:?} >", self.operation),
+            SprintItem::Range => {
+                let (min_number, max_number) = self.range;
+                format!("Numbers: < {} to {} >", min_number, max_number)
+            }
+            SprintItem::Start => "Start".to_string(),
+        }
+    }
+
+    /// Steps a setting forward or back, wrapping around at the ends
+    pub fn adjust(&mut self, item: SprintItem, direction: i32) {
+        match item {
+            SprintItem::Minutes => self.minutes = cycle(&SPRINT_MINUTES, self.minutes, direction),
+            SprintItem::Operation => {
+                self.operation = cycle(&SPRINT_OPERATIONS, self.operation, direction)
+            }
+            SprintItem::Range => self.range = cycle(&SPRINT_RANGES, self.range, direction),
+            SprintItem::Start => (),
+        }
+    }
+
+    /// What's left on the clock, e.g. "0:42"
+    pub fn clock(&self) -> String {
+        let seconds = (self.remaining / 1000.0).ceil().max(0.0) as u32;
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+
+    /// The lines for the end screen
+    pub fn summary(&self, score: &Score) -> Vec<String> {
+        vec![
+            format!(
+                "Problems Per Minute: {:.1}",
+                score.correct as f32 / self.minutes as f32
+            ),
+            format!("Accuracy: {}%", (score.accuracy() * 100.0).round() as i32),
+            format!("Correct: {}", score.correct),
+            format!("Missed: {}", self.missed),
+        ]
+    }
+}
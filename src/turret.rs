@@ -38,13 +38,18 @@ impl Scalable for Turret {
 }
 impl Turret {
     pub fn new(assets: &Assets, ctx: &mut Context) -> Turret {
+        Turret::new_at(0.5, assets, ctx)
+    }
+
+    /// A turret `x` of the way across the bottom of the screen
+    pub fn new_at(x: f32, assets: &Assets, ctx: &mut Context) -> Turret {
         let mut rng = rand::thread_rng();
         let mut explosions = Vec::new();
         for _ in 0..20 {
             let r1 = rng.gen_range(-0.05, 0.05);
             let r2 = rng.gen_range(-0.05, 0.05);
             let t = rng.gen_range(0.0, 1000.0);
-            explosions.push(Explosion::new(t, na::Point2::new(0.5 + r1, 0.9 + r2)));
+            explosions.push(Explosion::new(t, na::Point2::new(x + r1, 0.9 + r2)));
         }
 
         Turret {
@@ -55,12 +60,24 @@ impl Turret {
             state: TurretState::Resting,
             src_pixel_width: assets.turret.width() as f32,
             src_pixel_height: assets.turret.height() as f32,
-            pos: na::Point2::new(0.5, 0.9),
+            pos: na::Point2::new(x, 0.9),
         }
     }
 
     pub fn update(&mut self, _ctx: &mut Context, _dt: std::time::Duration) {}
 
+    /// Turns to face `target`, in percent of the screen like `pos`
+    pub fn aim_at(&mut self, target: na::Point2<f32>) {
+        let v1 = na::Vector2::new(0.0, -1.0);
+        let v2 = target - self.pos;
+        let angle = v2.angle(&v1);
+        self.rotation = if target[0] < self.pos[0] {
+            -angle
+        } else {
+            angle
+        };
+    }
+
     pub fn draw(&self, ctx: &mut Context, assets: &mut Assets) {
         let param = DrawParam::new()
             .color(WHITE)
@@ -69,11 +86,19 @@ impl Turret {
             .rotation(self.rotation)
             .dest(self.pixel_pos(graphics::size(ctx)));
         let _ = graphics::draw(ctx, &assets.turret, param);
-        self.text
-            .draw_horizontal_center(graphics::size(ctx).1 * 0.9, ctx);
+        // the answer being typed sits under the turret
+        let screen = graphics::size(ctx);
+        let text_width = self.text.dest_pixel_dimensions(screen).0;
+        let text_pos = na::Point2::new(self.pos[0] * screen.0 - text_width / 2.0, screen.1 * 0.9);
+        self.text.draw(text_pos, ctx);
     }
 
     pub fn draw_lives(&self, lives: usize, ctx: &mut Context, assets: &mut Assets) {
+        self.draw_lives_at(lives, 0.95, ctx, assets);
+    }
+
+    /// The spare guns in a row starting `left` of the way across the screen
+    pub fn draw_lives_at(&self, lives: usize, left: f32, ctx: &mut Context, assets: &mut Assets) {
         let scale = self.scale(graphics::size(ctx));
 
         for i in 0..lives {
@@ -82,7 +107,7 @@ impl Turret {
                 .scale(scale * 0.5)
                 .offset(na::Point2::new(0.5, 0.5))
                 .dest(to_screen_pos(
-                    (0.95 + 0.03 * i as f32, 0.925),
+                    (left + 0.03 * i as f32, 0.925),
                     graphics::size(ctx),
                 ));
             let _ = graphics::draw(ctx, &assets.turret, param);
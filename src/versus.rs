@@ -0,0 +1,118 @@
+use ggez::event::KeyCode;
+use rand::SeedableRng;
+
+use crate::alien::*;
+use crate::daily::SeededRng;
+use crate::endless::*;
+use crate::level::*;
+use crate::score::*;
+use crate::turret::*;
+
+pub const VERSUS_LIVES: usize = 3;
+/// Every this many right answers in a row sends an alien to the other side
+pub const SEND_STREAK: u32 = 3;
+pub const PLAYER_NAMES: [&str; 2] = ["Player One", "Player Two"];
+
+/// What a key does for whichever player it belongs to
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum VersusKey {
+    Char(char),
+    Back,
+    Fire,
+    NextTarget,
+}
+
+/// Player one types on the number row and player two on the numpad, so
+/// each gets its own fire, delete and target keys on their half of the keyboard
+pub fn versus_key(keycode: KeyCode) -> Option<(usize, VersusKey)> {
+    let row = [
+        KeyCode::Key0,
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+    ];
+    let pad = [
+        KeyCode::Numpad0,
+        KeyCode::Numpad1,
+        KeyCode::Numpad2,
+        KeyCode::Numpad3,
+        KeyCode::Numpad4,
+        KeyCode::Numpad5,
+        KeyCode::Numpad6,
+        KeyCode::Numpad7,
+        KeyCode::Numpad8,
+        KeyCode::Numpad9,
+    ];
+    let digit = |i: usize| VersusKey::Char(std::char::from_digit(i as u32, 10).unwrap_or('0'));
+    if let Some(i) = row.iter().position(|key| *key == keycode) {
+        return Some((0, digit(i)));
+    }
+    if let Some(i) = pad.iter().position(|key| *key == keycode) {
+        return Some((1, digit(i)));
+    }
+    match keycode {
+        KeyCode::Minus => Some((0, VersusKey::Char('-'))),
+        KeyCode::Back => Some((0, VersusKey::Back)),
+        KeyCode::Return => Some((0, VersusKey::Fire)),
+        KeyCode::Tab => Some((0, VersusKey::NextTarget)),
+        KeyCode::Subtract => Some((1, VersusKey::Char('-'))),
+        KeyCode::Decimal => Some((1, VersusKey::Back)),
+        KeyCode::NumpadEnter => Some((1, VersusKey::Fire)),
+        KeyCode::Add => Some((1, VersusKey::NextTarget)),
+        _ => None,
+    }
+}
+
+/// Where `x` of the way across a player's half is on the whole screen
+pub fn side_x(side: usize, x: f32) -> f32 {
+    (side as f32 + x) * 0.5
+}
+
+/// Both players get the same waves, so the rng for wave `number` of a
+/// match only depends on the match's seed
+pub fn versus_rng(seed: u64, number: usize) -> SeededRng {
+    SeededRng::seed_from_u64(seed.wrapping_mul(1000).wrapping_add(number as u64))
+}
+
+/// The level versus plays on, only its first wave is used for the keypad and
+/// picker, each side gets its waves from `versus_rng`
+pub fn versus_level(seed: u64) -> Level {
+    Level {
+        waves: vec![endless_wave(0, &mut versus_rng(seed, 0))],
+        background_file: "/spacebg3.jpg".to_string(),
+        title: "Versus".to_string(),
+        unlocked: [true; 4],
+        music_file: None,
+        stars: [0; 4],
+        unlock_rules: Vec::new(),
+    }
+}
+
+/// One player's half of the screen
+pub struct Side {
+    pub turret: Turret,
+    pub aliens: Vec<Alien>,
+    pub target: Option<usize>,
+    pub lives: usize,
+    pub score: Score,
+    pub wave: usize,
+}
+
+impl Side {
+    pub fn new(turret: Turret) -> Side {
+        Side {
+            turret,
+            aliens: Vec::new(),
+            target: None,
+            lives: VERSUS_LIVES,
+            score: Score::new(VERSUS_LIVES),
+            wave: 0,
+        }
+    }
+}
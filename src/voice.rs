@@ -0,0 +1,220 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Clips are one word each, e.g. `/voice/seven.ogg`
+pub const VOICE_DIR: &str = "/voice";
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+pub fn clip_path(word: &str) -> String {
+    format!("{}/{}.ogg", VOICE_DIR, word.replace('\'', ""))
+}
+
+fn words(list: &[&str]) -> Vec<String> {
+    list.iter().map(|word| word.to_string()).collect()
+}
+
+pub fn correct_words() -> Vec<String> {
+    words(&["correct"])
+}
+
+pub fn try_again_words() -> Vec<String> {
+    words(&["try", "again"])
+}
+
+/// 342 is "three hundred forty two"
+pub fn number_words(n: i64) -> Vec<String> {
+    if n < 0 {
+        let mut spoken = words(&["negative"]);
+        spoken.extend(number_words(-n));
+        return spoken;
+    }
+    let mut spoken = Vec::new();
+    let (big, rest) = if n >= 1_000_000 {
+        (Some((n / 1_000_000, "million")), n % 1_000_000)
+    } else if n >= 1000 {
+        (Some((n / 1000, "thousand")), n % 1000)
+    } else if n >= 100 {
+        (Some((n / 100, "hundred")), n % 100)
+    } else {
+        (None, n)
+    };
+    if let Some((count, name)) = big {
+        spoken.extend(number_words(count));
+        spoken.push(name.to_string());
+        if rest > 0 {
+            spoken.extend(number_words(rest));
+        }
+    } else if rest < 20 {
+        spoken.push(ONES[rest as usize].to_string());
+    } else {
+        spoken.push(TENS[rest as usize / 10].to_string());
+        if rest % 10 > 0 {
+            spoken.push(ONES[rest as usize % 10].to_string());
+        }
+    }
+    spoken
+}
+
+enum Token {
+    // the raw number with any $, cents, time or rounding marks
+    Number(String),
+    Word(String),
+    Symbol(char),
+}
+
+fn take_digits(chars: &mut Peekable<Chars>, into: &mut String) {
+    while let Some(c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        into.push(*c);
+        chars.next();
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_ascii_digit() || c == '$' {
+            let mut number = String::new();
+            if c == '$' {
+                number.push(c);
+                chars.next();
+            }
+            take_digits(&mut chars, &mut number);
+            if let Some(&mark) = chars.peek() {
+                if mark == '.' || mark == ':' {
+                    number.push(mark);
+                    chars.next();
+                    take_digits(&mut chars, &mut number);
+                }
+            }
+            if let Some(&mark) = chars.peek() {
+                if mark == '¢' || mark == 's' {
+                    number.push(mark);
+                    chars.next();
+                }
+            }
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if !c.is_alphabetic() && c != '\'' {
+                    break;
+                }
+                word.extend(c.to_lowercase());
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        } else {
+            if !c.is_whitespace() {
+                tokens.push(Token::Symbol(c));
+            }
+            chars.next();
+        }
+    }
+    tokens
+}
+
+fn parse_number(digits: &str) -> i64 {
+    digits.parse::<i64>().unwrap_or(0)
+}
+
+fn spoken_number(number: &str) -> Vec<String> {
+    if let Some(dollars) = number.strip_prefix('$') {
+        let mut parts = dollars.splitn(2, '.');
+        let whole = parse_number(parts.next().unwrap_or(""));
+        let cents = parse_number(parts.next().unwrap_or(""));
+        let mut spoken = number_words(whole);
+        spoken.push(if whole == 1 { "dollar" } else { "dollars" }.to_string());
+        if cents > 0 {
+            spoken.extend(number_words(cents));
+        }
+        spoken
+    } else if let Some(cents) = number.strip_suffix('¢') {
+        let mut spoken = number_words(parse_number(cents));
+        spoken.push("cents".to_string());
+        spoken
+    } else if number.contains(':') {
+        // 4:05 is "four oh five", 5:00 is "five o'clock"
+        let mut parts = number.splitn(2, ':');
+        let mut spoken = number_words(parse_number(parts.next().unwrap_or("")));
+        let minute = parse_number(parts.next().unwrap_or(""));
+        if minute == 0 {
+            spoken.push("o'clock".to_string());
+        } else {
+            if minute < 10 {
+                spoken.push("oh".to_string());
+            }
+            spoken.extend(number_words(minute));
+        }
+        spoken
+    } else {
+        number_words(parse_number(number.trim_end_matches('s')))
+    }
+}
+
+/// The words to read out for a problem, e.g. "7-3" is
+/// "seven minus three". Anything without a spoken form is skipped.
+pub fn spoken_words(text: &str) -> Vec<String> {
+    let tokens = tokenize(text);
+    let mut spoken = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let after_number = i > 0 && matches!(tokens[i - 1], Token::Number(_));
+        match &tokens[i] {
+            Token::Number(number) => spoken.extend(spoken_number(number)),
+            // X is the times sign on the aliens
+            Token::Word(word) if word == "x" => spoken.push("times".to_string()),
+            Token::Word(word) if word == "min" => spoken.push("minutes".to_string()),
+            Token::Word(word) => spoken.push(word.clone()),
+            Token::Symbol('-') if after_number => spoken.push("minus".to_string()),
+            Token::Symbol('-') => spoken.push("negative".to_string()),
+            Token::Symbol('+') => spoken.push("plus".to_string()),
+            Token::Symbol('/') => spoken.extend(words(&["divided", "by"])),
+            Token::Symbol('%') => spoken.push("percent".to_string()),
+            Token::Symbol('√') => spoken.extend(words(&["square", "root", "of"])),
+            Token::Symbol('□') => spoken.extend(words(&["compared", "to"])),
+            Token::Symbol('²') => spoken.push("squared".to_string()),
+            Token::Symbol('³') => spoken.push("cubed".to_string()),
+            Token::Symbol('→') => match tokens.get(i + 1) {
+                // "47 → 10s" rounds to the nearest ten
+                Some(Token::Number(place)) if place.ends_with('s') => {
+                    spoken.extend(words(&["rounded", "to", "the", "nearest"]));
+                    spoken.extend(number_words(parse_number(place.trim_end_matches('s'))));
+                    i += 1;
+                }
+                _ => spoken.push("to".to_string()),
+            },
+            Token::Symbol(_) => (),
+        }
+        i += 1;
+    }
+    spoken
+}
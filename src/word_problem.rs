@@ -0,0 +1,211 @@
+use rand::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+
+use crate::level::*;
+use crate::problem::*;
+
+pub const DEFAULT_TEMPLATE_FILE: &str = "resources/word_problems.json";
+
+/// A sentence with `{name}`, `{a}` and `{b}` placeholders. Templates are
+/// limited to the four basic operations. Like a wave's numbers, `a` and `b`
+/// go from their min up to but not including their max. For Divide, `a` is
+/// the range of the quotient and the total that gets shared out is filled
+/// in as `a * b`.
+#[derive(Deserialize, Serialize)]
+pub struct WordTemplate {
+    pub text: String,
+    pub operation: Operation,
+    pub a: (i32, i32),
+    pub b: (i32, i32),
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WordProblems {
+    pub names: Vec<String>,
+    pub templates: Vec<WordTemplate>,
+}
+
+impl WordProblems {
+    pub fn load_from_file(path: &str) -> WordProblems {
+        //if any of this fails, fall back to the built in templates
+        fn load_helper(path: &str) -> Result<WordProblems, String> {
+            let mut file = File::open(path).map_err(|e| format!("file not found\n {}", e))?;
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)
+                .map_err(|e| format!("file could not be read\n{}", e))?;
+            let word_problems: WordProblems = serde_json::from_slice(&buffer[..])
+                .map_err(|e| format!("file not valid\n{}", e))?;
+            if word_problems.names.is_empty() || word_problems.templates.is_empty() {
+                return Err("file has no names or no templates".to_string());
+            }
+            Ok(word_problems)
+        }
+
+        match load_helper(path) {
+            Ok(word_problems) => word_problems,
+            Err(msg) => {
+                println!(
+                    "Error loading word problem file {}.\nUsing default\n{}",
+                    path, msg
+                );
+                let word_problems = WordProblems::new();
+                // only write out the default file, never clobber a teacher's custom one
+                if path == DEFAULT_TEMPLATE_FILE {
+                    word_problems.save(path);
+                }
+                word_problems
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) {
+        fn save_helper(word_problems: &WordProblems, path: &str) -> Result<(), String> {
+            let serialized = serde_json::to_string_pretty(word_problems)
+                .map_err(|_| "couldn't serialize word problems")?;
+            let mut file =
+                File::create(path).map_err(|_| "couldn't create save file for word problems")?;
+            file.write_all(serialized.as_bytes())
+                .map_err(|_| "couldn't write to save file")?;
+            Ok(())
+        }
+        if let Err(msg) = save_helper(self, path) {
+            println!("{}", msg);
+        }
+    }
+
+    /// Fills in a random template, the sentence becomes the problem text.
+    pub fn gen_problem<R: Rng>(&self, difficulty: usize, rng: &mut R) -> Problem {
+        let template = &self.templates[rng.gen_range(0, self.templates.len())];
+        let name = &self.names[rng.gen_range(0, self.names.len())];
+        let scale = |(min, max): (i32, i32)| {
+            (
+                (min as f32 * MIN_NUMBER_DIFFICULTY[difficulty]) as i32,
+                (max as f32 * MAX_NUMBER_DIFFICULTY[difficulty]) as i32,
+            )
+        };
+        let (min_a, max_a) = scale(template.a);
+        let (min_b, max_b) = scale(template.b);
+        let mut a = gen_between(rng, min_a, max_a);
+        let mut b = gen_between(rng, min_b, max_b);
+
+        let answer = match template.operation {
+            Operation::Subtract => {
+                // never take away more than there was to begin with
+                if b > a {
+                    std::mem::swap(&mut a, &mut b);
+                }
+                a - b
+            }
+            Operation::Multiply => a * b,
+            Operation::Divide => {
+                b = b.max(1);
+                let quotient = a;
+                a = quotient * b;
+                quotient
+            }
+            _ => a + b,
+        };
+
+        let sentence = template
+            .text
+            .replace("{name}", name)
+            .replace("{a}", &a.to_string())
+            .replace("{b}", &b.to_string());
+        Problem {
+            text: sentence,
+            answer: Answer::Number(answer),
+            operands: Some((a, b)),
+        }
+    }
+
+    pub fn new() -> WordProblems {
+        let template = |text: &str, operation, a, b| WordTemplate {
+            text: text.to_string(),
+            operation,
+            a,
+            b,
+        };
+        WordProblems {
+            names: ["Ada", "Theo", "Maya", "Sam", "Priya", "Leo", "Zoe", "Omar"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            templates: vec![
+                template(
+                    "{name} has {a} apples and gets {b} more. How many apples does {name} have now?",
+                    Operation::Add,
+                    (1, 11),
+                    (1, 11),
+                ),
+                template(
+                    "{name} saw {a} rockets in the morning and {b} at night. How many rockets did {name} see?",
+                    Operation::Add,
+                    (1, 11),
+                    (1, 11),
+                ),
+                template(
+                    "{name} had {a} stickers and gave {b} to a friend. How many stickers are left?",
+                    Operation::Subtract,
+                    (2, 11),
+                    (1, 11),
+                ),
+                template(
+                    "There were {a} aliens on a ship. {b} flew away. How many aliens are still on the ship?",
+                    Operation::Subtract,
+                    (2, 11),
+                    (1, 11),
+                ),
+                template(
+                    "{name} has {a} bags with {b} marbles in each bag. How many marbles is that?",
+                    Operation::Multiply,
+                    (1, 6),
+                    (1, 6),
+                ),
+                template(
+                    "{name} shares {a} cookies equally between {b} friends. How many cookies does each friend get?",
+                    Operation::Divide,
+                    (1, 6),
+                    (1, 6),
+                ),
+            ],
+        }
+    }
+}
+
+/// Every template file the levels use, loaded once at startup rather than
+/// for each wave
+pub struct TemplateCache {
+    files: HashMap<String, WordProblems>,
+}
+
+impl TemplateCache {
+    pub fn load(levels: &[Level]) -> TemplateCache {
+        let mut files = HashMap::new();
+        files.insert(
+            DEFAULT_TEMPLATE_FILE.to_string(),
+            WordProblems::load_from_file(DEFAULT_TEMPLATE_FILE),
+        );
+        for level in levels {
+            for group in level.waves.iter().flat_map(|wave| &wave.groups) {
+                if let Some(path) = &group.template_file {
+                    if !files.contains_key(path) {
+                        files.insert(path.clone(), WordProblems::load_from_file(path));
+                    }
+                }
+            }
+        }
+        TemplateCache { files }
+    }
+
+    /// The templates from `path`, or the default ones when it's None
+    pub fn get(&self, path: Option<&str>) -> &WordProblems {
+        let path = path.unwrap_or(DEFAULT_TEMPLATE_FILE);
+        match self.files.get(path) {
+            Some(word_problems) => word_problems,
+            None => &self.files[DEFAULT_TEMPLATE_FILE],
+        }
+    }
+}